@@ -2,10 +2,22 @@
 //!
 
 use core::fmt;
+use std::str::FromStr;
 
 use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
 
+use super::sanitise::{account_component, is_account_component, transliterate, Transliterations};
+
+/// The placeholders an account name template may use.
+pub const TEMPLATE_PLACEHOLDERS: [&str; 5] = [
+    "{country}",
+    "{institution}",
+    "{account}",
+    "{sub_account}",
+    "{category}",
+];
+
 /// Represents permissable Beancount account types.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash, strum_macros::Display)]
 pub enum AccountType {
@@ -18,20 +30,76 @@ pub enum AccountType {
 
 /// Represents a Beancount account.
 ///
+/// In the user settings an account is written as its Beancount name, e.g.
+/// `Assets:GBP:Amex:Gold`, or as its parts.
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[serde(try_from = "AccountConfig", into = "String")]
 pub struct Account {
     pub(crate) account_type: AccountType,
     pub(crate) country: String,
     pub(crate) institution: String,
     pub(crate) account: String,
     pub(crate) sub_account: Option<String>,
-    pub(crate) transaction_id: Option<String>,
+    /// The full name, when the account doesn't follow the default hierarchy, e.g. one named by
+    /// a template from the user settings.
+    pub(crate) name: Option<String>,
+}
+
+impl Account {
+    /// Name the account by `template`, e.g. `Expenses:{category}`.
+    ///
+    /// The placeholders are `{country}`, `{institution}`, `{account}` and `{sub_account}`, or
+    /// `{category}` for an expense's sub-account. A component left empty, such as
+    /// `{sub_account}` of an account without one, is dropped. It is an error for the name to be
+    /// left without a component after its type, or with one that isn't valid.
+    pub fn apply_template(&mut self, template: &str) -> Result<(), String> {
+        let country = self.country.to_case(Case::Upper);
+        let component = |value: &str| match value {
+            "" => String::new(),
+            value => account_component(value),
+        };
+        let institution = component(&self.institution);
+        let account = component(&self.account);
+        let sub_account = component(self.sub_account.as_deref().unwrap_or_default());
+
+        let name = template
+            .split(':')
+            .map(|part| {
+                part.replace("{country}", &country)
+                    .replace("{institution}", &institution)
+                    .replace("{account}", &account)
+                    .replace("{sub_account}", &sub_account)
+                    .replace("{category}", &sub_account)
+            })
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<String>>()
+            .join(":");
+
+        Account::from_str(&name)?;
+        self.name = Some(name);
+
+        Ok(())
+    }
+
+    /// Apply the user's transliteration `rules` to the names in the account.
+    pub fn transliterate(&mut self, rules: &Transliterations) {
+        self.institution = transliterate(&self.institution, rules);
+        self.account = transliterate(&self.account, rules);
+        self.sub_account = self
+            .sub_account
+            .as_ref()
+            .map(|sub_account| transliterate(sub_account, rules));
+    }
 }
 
 impl fmt::Display for Account {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = &self.name {
+            return write!(f, "{}", name);
+        }
+
         let label = match &self.sub_account {
-            Some(sub_account) => format!(":{}", sub_account.to_case(Case::Pascal)),
+            Some(sub_account) => format!(":{}", account_component(sub_account)),
             None => String::new(),
         };
         match &self.account_type {
@@ -40,7 +108,7 @@ impl fmt::Display for Account {
                     f,
                     "{}:{}",
                     self.account_type,
-                    self.account.to_case(Case::Pascal),
+                    account_component(&self.account),
                 )
             }
             _ => {
@@ -49,11 +117,241 @@ impl fmt::Display for Account {
                     "{}:{}:{}:{}{}",
                     self.account_type,
                     self.country.to_case(Case::Upper),
-                    self.institution.to_case(Case::Pascal),
-                    self.account.to_case(Case::Pascal),
+                    account_component(&self.institution),
+                    account_component(&self.account),
                     label,
                 )
             }
         }
     }
 }
+
+/// Parse an account name of the form `Type:Country:Institution:Account[:SubAccount]`, or
+/// `Equity:Account`. Names in any other hierarchy, such as those from an account name template,
+/// are kept as written.
+impl FromStr for Account {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = name.split(':').collect();
+
+        if let Some(part) = parts[1..].iter().find(|part| !is_account_component(part)) {
+            return Err(format!(
+                "invalid account component \"{}\" in \"{}\"",
+                part, name
+            ));
+        }
+
+        let account_type = match parts[0] {
+            "Assets" => AccountType::Assets,
+            "Liabilities" => AccountType::Liabilities,
+            "Income" => AccountType::Income,
+            "Expenses" => AccountType::Expenses,
+            "Equity" => AccountType::Equity,
+            other => return Err(format!("unknown account type \"{}\"", other)),
+        };
+
+        let mut account = match parts.as_slice() {
+            [] | [_] => return Err(format!("account \"{}\" has no name after its type", name)),
+            [_, account] if account_type == AccountType::Equity => Account {
+                account_type,
+                country: String::new(),
+                institution: String::new(),
+                account: account.to_string(),
+                sub_account: None,
+                name: None,
+            },
+            [_, country, institution, account, sub_account @ ..]
+                if account_type != AccountType::Equity && sub_account.len() <= 1 =>
+            {
+                Account {
+                    account_type,
+                    country: country.to_string(),
+                    institution: institution.to_string(),
+                    account: account.to_string(),
+                    sub_account: sub_account.first().map(|s| s.to_string()),
+                    name: None,
+                }
+            }
+            [.., last] => Account {
+                account_type,
+                country: String::new(),
+                institution: String::new(),
+                account: last.to_string(),
+                sub_account: None,
+                name: Some(name.to_string()),
+            },
+        };
+
+        // e.g. a country that isn't upper case, which the default hierarchy would change
+        if account.to_string() != name {
+            account.name = Some(name.to_string());
+        }
+
+        Ok(account)
+    }
+}
+
+impl From<Account> for String {
+    fn from(account: Account) -> Self {
+        account.to_string()
+    }
+}
+
+/// An account as written in the user settings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountConfig {
+    /// The account's Beancount name, e.g. `Assets:GBP:Amex:Gold`.
+    Name(String),
+    /// The account's parts, as written before accounts could be named.
+    Parts {
+        account_type: AccountType,
+        country: String,
+        institution: String,
+        account: String,
+        sub_account: Option<String>,
+    },
+}
+
+impl TryFrom<AccountConfig> for Account {
+    type Error = String;
+
+    fn try_from(config: AccountConfig) -> Result<Self, Self::Error> {
+        match config {
+            AccountConfig::Name(name) => name.parse(),
+            AccountConfig::Parts {
+                account_type,
+                country,
+                institution,
+                account,
+                sub_account,
+            } => {
+                // the parts are kept as written, e.g. "NSI Premium Bonds" to match a payee, so
+                // only the name they render to must be valid
+                let account = Account {
+                    account_type,
+                    country,
+                    institution,
+                    account,
+                    sub_account,
+                    name: None,
+                };
+                Account::from_str(&account.to_string())?;
+
+                Ok(account)
+            }
+        }
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_account_names() {
+        let names = [
+            "Assets:GBP:Amex:Gold",
+            "Expenses:GBP:Monzo:Personal:EatingOut",
+            "Equity:OpeningBalances",
+            "Expenses:Groceries",
+            "Assets:Monzo:Joint",
+        ];
+
+        for name in names {
+            let account = Account::from_str(name).unwrap();
+
+            assert_eq!(account.to_string(), name);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_account_names() {
+        let names = [
+            "Expenses",
+            "Savings:GBP:Monzo:Personal",
+            "Assets:GBP:Monzo:personal",
+            "Assets:GBP:Monzo:Eating Out",
+            "Assets::Monzo:Personal",
+        ];
+
+        for name in names {
+            assert!(Account::from_str(name).is_err(), "for {:?}", name);
+        }
+    }
+
+    #[test]
+    fn should_apply_account_name_templates() {
+        let groceries = Account::from_str("Expenses:GBP:Monzo:Personal:Groceries").unwrap();
+        let personal = Account::from_str("Assets:GBP:Monzo:Personal").unwrap();
+
+        let mut account = groceries.clone();
+        account.apply_template("Expenses:{category}").unwrap();
+        assert_eq!(account.to_string(), "Expenses:Groceries");
+
+        let mut account = personal.clone();
+        account
+            .apply_template("Assets:{institution}:{account}:{sub_account}")
+            .unwrap();
+        assert_eq!(account.to_string(), "Assets:Monzo:Personal");
+
+        assert!(personal
+            .clone()
+            .apply_template("Assets:{sub_account}")
+            .is_err());
+        assert!(groceries
+            .clone()
+            .apply_template("Expenses:my{category}")
+            .is_err());
+    }
+
+    #[test]
+    fn should_deserialise_names_and_parts() {
+        // Arrange
+        let yaml = r#"
+- Liabilities:GBP:Amex:Gold
+- account_type: Liabilities
+  country: GBP
+  institution: Amex
+  account: Gold
+  sub_account: null
+"#;
+
+        // Act
+        let accounts: Vec<Account> = serde_yaml::from_str(yaml).unwrap();
+
+        // Assert
+        assert_eq!(accounts[0].account, "Gold");
+        assert_eq!(accounts[0].to_string(), accounts[1].to_string());
+    }
+
+    #[test]
+    fn should_report_invalid_account_in_settings() {
+        let result: Result<Account, _> = serde_yaml::from_str("Assets:GBP:Amex:gold");
+
+        let error = result.unwrap_err().to_string();
+        assert!(
+            error.contains("invalid account component \"gold\""),
+            "{}",
+            error
+        );
+
+        // the parts are named as generated accounts are, so the same account loads as parts
+        let parts = "{account_type: Assets, country: GBP, institution: Amex, account: gold}";
+        let account: Account = serde_yaml::from_str(parts).unwrap();
+        assert_eq!(account.to_string(), "Assets:GBP:Amex:Gold");
+    }
+
+    #[test]
+    fn should_keep_parts_as_written_in_settings() {
+        let yaml = "{account_type: Assets, country: GBP, institution: my bank, account: NSI Premium Bonds}";
+
+        let account: Account = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(account.account, "NSI Premium Bonds");
+        assert_eq!(account.to_string(), "Assets:GBP:MyBank:NsiPremiumBonds");
+    }
+}
@@ -16,6 +16,9 @@ pub struct DataFilePaths {
     pub import_dir: PathBuf,
     pub main_file: PathBuf,
     pub config_file: PathBuf,
+    pub store_file: PathBuf,
+    pub quarantine_file: PathBuf,
+    pub balances_file: PathBuf,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -35,9 +38,15 @@ impl DataFilePaths {
 
         const MAINFILE_NAME: &str = "main.beancount";
         const CONFIG_FILE_NAME: &str = "beancount.yaml";
+        const STORE_FILE_NAME: &str = "transactions.sqlite";
+        const QUARANTINE_FILE_NAME: &str = "quarantine.csv";
+        const BALANCES_FILE_NAME: &str = "balances.csv";
 
         let main_file = data_dir.join(MAINFILE_NAME);
         let config_file = data_dir.join(CONFIG_FILE_NAME);
+        let store_file = data_dir.join(STORE_FILE_NAME);
+        let quarantine_file = data_dir.join(QUARANTINE_FILE_NAME);
+        let balances_file = data_dir.join(BALANCES_FILE_NAME);
 
         if let InitFlag::Initialize = init_flag {
             // create directtories
@@ -64,6 +73,9 @@ impl DataFilePaths {
             import_dir,
             main_file,
             config_file,
+            store_file,
+            quarantine_file,
+            balances_file,
         })
     }
 }
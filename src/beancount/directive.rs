@@ -3,7 +3,11 @@
 use chrono::NaiveDate;
 use convert_case::{Case, Casing};
 
-use super::{account::Account, transaction::Transaction as BeanTransaction};
+use super::{
+    account::Account,
+    money::{exponent, Decimal},
+    transaction::{format_metadata, quote, Metadata, Transaction as BeanTransaction},
+};
 
 type Comment = String;
 
@@ -13,10 +17,86 @@ pub enum Directive {
     Option(String, String),
     Include(String),
     Comment(String),
-    Open(NaiveDate, Account, Option<Comment>),
+    /// Opens an account for its own currency and any other currencies listed.
+    Open(NaiveDate, Account, Vec<String>, Option<Comment>),
     Close(NaiveDate, Account, Option<Comment>),
     Transaction(Box<BeanTransaction>),
-    _Balance(NaiveDate, Account),
+    /// Asserts the balance of an account at the start of a day.
+    Balance(NaiveDate, Account, Decimal, String),
+    /// The price of one unit of a commodity in a currency, e.g. `1.27` USD for GBP.
+    Price(NaiveDate, String, Decimal, String, Metadata),
+    /// Fills the first account up to its next balance assertion from the second.
+    Pad(NaiveDate, Account, Account, Metadata),
+    /// A dated note on an account.
+    Note(NaiveDate, Account, String, Metadata),
+    /// A file, such as a receipt or statement, attached to an account.
+    Document(NaiveDate, Account, String, Metadata),
+    /// The value of an event, e.g. `"location"` becoming `"Paris"`, from a date on.
+    Event(NaiveDate, String, String, Metadata),
+    /// Declares a commodity or currency.
+    Commodity(NaiveDate, String, Metadata),
+    /// A named query for Fava and `bean-query`.
+    Query(NaiveDate, String, String, Metadata),
+    /// A directive for plugins and Fava extensions, with its type and values.
+    Custom(NaiveDate, String, Vec<CustomValue>, Metadata),
+}
+
+/// A value of a `custom` directive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CustomValue {
+    String(String),
+    Account(Account),
+    Amount(Decimal, String),
+    Number(Decimal),
+    Bool(bool),
+    Date(NaiveDate),
+}
+
+impl Directive {
+    /// The accounts the directive refers to, to rewrite their names.
+    pub fn accounts_mut(&mut self) -> Vec<&mut Account> {
+        match self {
+            Directive::Open(_, account, _, _)
+            | Directive::Close(_, account, _)
+            | Directive::Balance(_, account, _, _)
+            | Directive::Note(_, account, _, _)
+            | Directive::Document(_, account, _, _) => vec![account],
+            Directive::Pad(_, account, source, _) => vec![account, source],
+            Directive::Transaction(transaction) => transaction
+                .postings
+                .iter_mut()
+                .map(|posting| &mut posting.account)
+                .collect(),
+            Directive::Custom(_, _, values, _) => values
+                .iter_mut()
+                .filter_map(|value| match value {
+                    CustomValue::Account(account) => Some(account),
+                    _ => None,
+                })
+                .collect(),
+            Directive::Option(..)
+            | Directive::Include(..)
+            | Directive::Comment(..)
+            | Directive::Price(..)
+            | Directive::Event(..)
+            | Directive::Commodity(..)
+            | Directive::Query(..) => vec![],
+        }
+    }
+}
+
+impl CustomValue {
+    fn to_formatted_string(&self) -> String {
+        match self {
+            CustomValue::String(value) => quote(value),
+            CustomValue::Account(account) => account.to_string(),
+            CustomValue::Amount(amount, currency) => format!("{} {}", amount, currency),
+            CustomValue::Number(number) => number.to_string(),
+            CustomValue::Bool(true) => "TRUE".to_string(),
+            CustomValue::Bool(false) => "FALSE".to_string(),
+            CustomValue::Date(date) => date.to_string(),
+        }
+    }
 }
 
 impl Directive {
@@ -24,14 +104,20 @@ impl Directive {
     pub fn to_formatted_string(&self) -> String {
         let account_width = 50;
         match self {
-            Directive::Include(file) => format!("include \"{}\"\n", file),
+            Directive::Include(file) => format!("include {}\n", quote(file)),
 
-            Directive::Option(key, value) => format!("option \"{}\" \"{}\"\n", key, value),
+            Directive::Option(key, value) => format!("option {} {}\n", quote(key), quote(value)),
 
             Directive::Comment(comment) => format!("\n* {}\n\n", comment.to_case(Case::Title)),
 
-            Directive::Open(date, account, comment) => {
-                let currency = &account.country;
+            Directive::Open(date, account, other_currencies, comment) => {
+                // an account named outside the default hierarchy may have no currency
+                let currency = std::iter::once(&account.country)
+                    .chain(other_currencies)
+                    .filter(|currency| !currency.is_empty())
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(",");
                 let comment = match comment {
                     Some(c) => format!("; {c}.\n"),
                     None => String::new(),
@@ -62,8 +148,87 @@ impl Directive {
                 )
             }
 
-            Directive::_Balance(_date, _account) => {
-                todo!()
+            Directive::Balance(date, account, amount, currency) => {
+                let precision = exponent(currency) as usize;
+                format!(
+                    "{} balance {:account_width$} {:>10.precision$} {}\n",
+                    date,
+                    account.to_string(),
+                    amount,
+                    currency
+                )
+            }
+
+            Directive::Price(date, commodity, price, currency, metadata) => {
+                format!(
+                    "{} price {} {} {}\n{}",
+                    date,
+                    commodity,
+                    price,
+                    currency,
+                    format_metadata(metadata, "  ")
+                )
+            }
+
+            Directive::Pad(date, account, source, metadata) => format!(
+                "{} pad {} {}\n{}",
+                date,
+                account,
+                source,
+                format_metadata(metadata, "  ")
+            ),
+
+            Directive::Note(date, account, note, metadata) => format!(
+                "{} note {} {}\n{}",
+                date,
+                account,
+                quote(note),
+                format_metadata(metadata, "  ")
+            ),
+
+            Directive::Document(date, account, path, metadata) => format!(
+                "{} document {} {}\n{}",
+                date,
+                account,
+                quote(path),
+                format_metadata(metadata, "  ")
+            ),
+
+            Directive::Event(date, event, value, metadata) => format!(
+                "{} event {} {}\n{}",
+                date,
+                quote(event),
+                quote(value),
+                format_metadata(metadata, "  ")
+            ),
+
+            Directive::Commodity(date, commodity, metadata) => format!(
+                "{} commodity {}\n{}",
+                date,
+                commodity,
+                format_metadata(metadata, "  ")
+            ),
+
+            Directive::Query(date, name, query, metadata) => format!(
+                "{} query {} {}\n{}",
+                date,
+                quote(name),
+                quote(query),
+                format_metadata(metadata, "  ")
+            ),
+
+            Directive::Custom(date, custom, values, metadata) => {
+                let values: String = values
+                    .iter()
+                    .map(|value| format!(" {}", value.to_formatted_string()))
+                    .collect();
+                format!(
+                    "{} custom {}{}\n{}",
+                    date,
+                    quote(custom),
+                    values,
+                    format_metadata(metadata, "  ")
+                )
             }
         }
     }
@@ -87,10 +252,10 @@ mod tests {
             institution: "Monzo".to_string(),
             account: "Personal".to_string(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         };
         // Act
-        let directive = Directive::Open(date, account, None);
+        let directive = Directive::Open(date, account, vec![], None);
         // Assert
         assert_eq!(
             directive.to_formatted_string(),
@@ -108,11 +273,11 @@ mod tests {
             institution: "Monzo".to_string(),
             account: "Personal".to_string(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         };
         let comment = Some("Initial Deposit".to_string());
         // Act
-        let directive = Directive::Open(date, account, comment);
+        let directive = Directive::Open(date, account, vec![], comment);
         // Assert
         assert_eq!(
             directive.to_formatted_string(),
@@ -130,7 +295,7 @@ mod tests {
             institution: "Monzo".to_string(),
             account: "Personal".to_string(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         };
         // Act
         let directive = Directive::Close(date, account, None);
@@ -141,6 +306,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn balance_directive() {
+        // Arrange
+        let date = NaiveDate::from_ymd_opt(2024, 6, 13).unwrap();
+        let account = Account {
+            account_type: AccountType::Assets,
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            account: "Personal".to_string(),
+            sub_account: None,
+            name: None,
+        };
+        // Act
+        let directive =
+            Directive::Balance(date, account, Decimal::new(123456, 2), "GBP".to_string());
+        // Assert
+        assert_eq!(
+            directive.to_formatted_string(),
+            "2024-06-13 balance Assets:GBP:Monzo:Personal                             1234.56 GBP\n"
+        );
+    }
+
+    #[test]
+    fn price_directive() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 13).unwrap();
+        let directive = Directive::Price(
+            date,
+            "GBP".to_string(),
+            Decimal::new(127, 2),
+            "USD".to_string(),
+            Metadata::from([("source".to_string(), "ECB".to_string())]),
+        );
+        assert_eq!(
+            directive.to_formatted_string(),
+            "2024-06-13 price GBP 1.27 USD\n  source: \"ECB\"\n"
+        );
+    }
+
+    #[test]
+    fn account_directives_with_metadata() {
+        // Arrange
+        let date = NaiveDate::from_ymd_opt(2024, 6, 13).unwrap();
+        let account = Account {
+            account_type: AccountType::Assets,
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            account: "Personal".to_string(),
+            sub_account: None,
+            name: None,
+        };
+        let equity = Account {
+            account_type: AccountType::Equity,
+            country: "GBP".to_string(),
+            institution: String::new(),
+            account: "OpeningBalances".to_string(),
+            sub_account: None,
+            name: None,
+        };
+        let directives = [
+            Directive::Pad(date, account.clone(), equity, Metadata::new()),
+            Directive::Note(
+                date,
+                account.clone(),
+                "Called about the \"overdraft\"".to_string(),
+                Metadata::new(),
+            ),
+            Directive::Document(
+                date,
+                account,
+                "documents/receipt.pdf".to_string(),
+                Metadata::from([("monzo-id".to_string(), "tx_0001".to_string())]),
+            ),
+        ];
+
+        // Act
+        let formatted: String = directives.iter().map(|d| d.to_formatted_string()).collect();
+
+        // Assert
+        assert_eq!(
+            formatted,
+            r#"2024-06-13 pad Assets:GBP:Monzo:Personal Equity:OpeningBalances
+2024-06-13 note Assets:GBP:Monzo:Personal "Called about the \"overdraft\""
+2024-06-13 document Assets:GBP:Monzo:Personal "documents/receipt.pdf"
+  monzo-id: "tx_0001"
+"#
+        );
+    }
+
+    #[test]
+    fn custom_directive() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 13).unwrap();
+        let directive = Directive::Custom(
+            date,
+            "budget".to_string(),
+            vec![
+                CustomValue::String("Groceries".to_string()),
+                CustomValue::Amount(Decimal::new(25000, 2), "GBP".to_string()),
+                CustomValue::Bool(true),
+                CustomValue::Date(date),
+            ],
+            Metadata::new(),
+        );
+        assert_eq!(
+            directive.to_formatted_string(),
+            "2024-06-13 custom \"budget\" \"Groceries\" 250.00 GBP TRUE 2024-06-13\n"
+        );
+    }
+
     #[test]
     fn close_directive_comment() {
         // Arrange
@@ -151,7 +424,7 @@ mod tests {
             institution: "Monzo".to_string(),
             account: "Personal".to_string(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         };
         let comment = Some("To Close".to_string());
         // Act
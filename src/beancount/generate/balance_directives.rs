@@ -0,0 +1,126 @@
+//! Generate balance assertions from the user's balances file.
+//!
+//! The balances file is a CSV in the data directory with the closing balance of an account on a
+//! statement date:
+//!
+//! ```text
+//! date,account,balance,currency
+//! 2024-06-30,Assets:GBP:Monzo:Personal,1234.56,GBP
+//! ```
+//!
+//! Beancount checks a balance at the start of its day, so each balance is asserted on the day
+//! after the statement date.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{Days, NaiveDate};
+use csv::Reader;
+use serde::Deserialize;
+
+use crate::beancount::account::Account;
+use crate::beancount::directive::Directive;
+use crate::beancount::money::Decimal;
+use crate::error::AppError as Error;
+
+#[derive(Debug, Deserialize)]
+struct BalanceRecord {
+    date: NaiveDate,
+    account: String,
+    balance: Decimal,
+    currency: String,
+}
+
+/// The balance assertions in `balances_file`, or none if the file doesn't exist.
+pub(crate) fn balance_directives(balances_file: &Path) -> Result<Vec<Directive>, Error> {
+    if !balances_file.exists() {
+        return Ok(vec![]);
+    }
+
+    let file_error = |message: String| {
+        Error::ApplicationError(format!("{}: {}", balances_file.display(), message))
+    };
+
+    let mut reader = Reader::from_path(balances_file).map_err(|e| file_error(e.to_string()))?;
+    let mut directives: Vec<Directive> = Vec::new();
+
+    for record in reader.deserialize() {
+        let record: BalanceRecord = record.map_err(|e| file_error(e.to_string()))?;
+        let account = Account::from_str(&record.account).map_err(file_error)?;
+
+        directives.push(Directive::Balance(
+            day_after(record.date),
+            account,
+            record.balance,
+            record.currency,
+        ));
+    }
+
+    if !directives.is_empty() {
+        directives.insert(0, Directive::Comment("balances".to_string()));
+    }
+
+    Ok(directives)
+}
+
+/// The date to assert a balance taken at the end of `statement_date`.
+pub(crate) fn day_after(statement_date: NaiveDate) -> NaiveDate {
+    statement_date + Days::new(1)
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn should_assert_balances_on_the_day_after_the_statement() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let balances_file = dir.child("balances.csv");
+        fs::write(
+            &balances_file,
+            "date,account,balance,currency\n2024-06-30,Assets:GBP:Monzo:Personal,1234.56,GBP\n",
+        )
+        .unwrap();
+
+        // Act
+        let directives = balance_directives(&balances_file).unwrap();
+
+        // Assert
+        assert_eq!(directives.len(), 2);
+        assert_eq!(
+            directives[1].to_formatted_string(),
+            "2024-07-01 balance Assets:GBP:Monzo:Personal                             1234.56 GBP\n"
+        );
+    }
+
+    #[test]
+    fn should_report_unparseable_accounts() {
+        let dir = TempDir::new().unwrap();
+        let balances_file = dir.child("balances.csv");
+        fs::write(
+            &balances_file,
+            "date,account,balance,currency\n2024-06-30,Assets:GBP:Monzo:personal,1234.56,GBP\n",
+        )
+        .unwrap();
+
+        let result = balance_directives(&balances_file);
+
+        assert!(matches!(result, Err(Error::ApplicationError(_))));
+    }
+
+    #[test]
+    fn should_skip_missing_balances_file() {
+        let dir = TempDir::new().unwrap();
+
+        let directives = balance_directives(&dir.child("balances.csv")).unwrap();
+
+        assert!(directives.is_empty());
+    }
+}
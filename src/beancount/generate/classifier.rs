@@ -4,6 +4,7 @@ use std::collections::HashSet;
 
 use crate::beancount::account::Account as BeancountAccount;
 use crate::beancount::google::transactions::Transaction as GoogleTransaction;
+use crate::beancount::source::POT_TRANSFER;
 use crate::error::AppError as Error;
 
 #[derive(Debug, PartialEq)]
@@ -18,8 +19,8 @@ pub(crate) enum Classification {
 }
 
 pub(crate) fn classify_transaction(
-    asset_accounts: &Vec<BeancountAccount>,
-    income_accounts: &Vec<BeancountAccount>,
+    asset_accounts: &[BeancountAccount],
+    income_accounts: &[BeancountAccount],
     tx: &GoogleTransaction,
 ) -> Result<Option<Classification>, Error> {
     match tx.category.as_str() {
@@ -40,7 +41,7 @@ pub(crate) fn classify_transaction(
         "Savings" => Ok(Some(Classification::Savings)),
 
         "Transfers" => {
-            if tx.payment_type == "Pot transfer" {
+            if tx.payment_type == POT_TRANSFER {
                 return Ok(Some(Classification::TransferPot));
             }
 
@@ -56,6 +57,29 @@ pub(crate) fn classify_transaction(
     }
 }
 
+/// Why the classification of `tx` may be wrong, or `None` when there's no doubt about it.
+///
+/// Generation flags these transactions with `!` so they can be reviewed in Fava.
+pub(crate) fn review_reason(
+    classification: Option<&Classification>,
+    tx: &GoogleTransaction,
+) -> Option<String> {
+    match (classification, tx.category.as_str()) {
+        (Some(Classification::TransferOpeningBalance), "Income") => Some(
+            "Income noted as an \"Account Switch\" was posted as an opening balance".to_string(),
+        ),
+        (Some(Classification::TransferOpeningBalance), _) => Some(format!(
+            "Transfer with \"{}\", which isn't a configured asset account, was posted as an opening balance",
+            tx.name
+        )),
+        (None, _) => Some(format!(
+            "Category \"{}\" wasn't classified, so it was posted as spending",
+            tx.category
+        )),
+        (Some(_), _) => None,
+    }
+}
+
 fn is_custom_transfer(tx: &GoogleTransaction) -> bool {
     tx.notes
         .as_ref()
@@ -63,13 +87,13 @@ fn is_custom_transfer(tx: &GoogleTransaction) -> bool {
         .starts_with("Account Switch")
 }
 
-fn is_asset_account(accounts: &Vec<BeancountAccount>, account: &str) -> bool {
+fn is_asset_account(accounts: &[BeancountAccount], account: &str) -> bool {
     let asset_accounts = get_filtered_asset_accounts(accounts).unwrap();
     asset_accounts.iter().any(|a| a.account == account)
 }
 
 fn asset_account_finder(
-    accounts: &Vec<BeancountAccount>,
+    accounts: &[BeancountAccount],
     account_to_find: &str,
 ) -> Option<BeancountAccount> {
     let filtered_assets = get_filtered_asset_accounts(accounts).unwrap();
@@ -85,7 +109,7 @@ fn asset_account_finder(
 }
 
 fn get_filtered_asset_accounts(
-    assets: &Vec<BeancountAccount>,
+    assets: &[BeancountAccount],
 ) -> Result<Vec<BeancountAccount>, Error> {
     // FIXME: This is a temporary solution to filter out unwanted accounts. Refactor to use config data.
     let unwanted_accounts = ["Business", "Personal"];
@@ -99,14 +123,14 @@ fn get_filtered_asset_accounts(
     Ok(unique_accounts.into_iter().collect())
 }
 
-fn is_income_account(accounts: &Vec<BeancountAccount>, account: &str) -> bool {
+fn is_income_account(accounts: &[BeancountAccount], account: &str) -> bool {
     let income_accounts = get_filtered_income_accounts(accounts).unwrap();
     income_accounts.iter().any(|a| a.account == account)
 }
 
 /// Find an income account in the Beancount configuration with the name `accouunt_to_find`.
 fn income_account_finder(
-    accounts: &Vec<BeancountAccount>,
+    accounts: &[BeancountAccount],
     account_to_find: &str,
 ) -> Option<BeancountAccount> {
     let filtered_income = get_filtered_income_accounts(accounts).unwrap();
@@ -123,7 +147,7 @@ fn income_account_finder(
 
 /// Get the income accounts from config and remove the main accounts.
 fn get_filtered_income_accounts(
-    incomes: &Vec<BeancountAccount>,
+    incomes: &[BeancountAccount],
 ) -> Result<Vec<BeancountAccount>, Error> {
     // FIXME: This is a temporary solution to filter out unwanted accounts. Refactor to use config data.
     let unwanted_accounts = ["Business", "Personal"];
@@ -141,11 +165,10 @@ fn get_filtered_income_accounts(
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
-
     use chrono::NaiveDate;
 
-    use crate::beancount::Beancount;
+    use crate::beancount::account::AccountType;
+    use crate::beancount::money::from_minor;
 
     use super::*;
 
@@ -159,13 +182,15 @@ mod tests {
             payment_type: "Faster payment".to_string(),
             name: "MPB EUROPE".to_string(),
             category: "Income".to_string(),
-            amount: 19100,
+            amount: from_minor(19100, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: 19100,
+            local_amount: from_minor(19100, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("MPB TX-UK-7836178".to_string()),
             description: Some("MPB TX-UK-7836178".to_string()),
             category_split: None,
+            pot: None,
+            sheet: None,
         };
 
         // Act
@@ -185,13 +210,15 @@ mod tests {
             payment_type: "Bacs (Direct Credit)".to_string(),
             name: "Bp Pension Trustee".to_string(),
             category: "Income".to_string(),
-            amount: 225945,
+            amount: from_minor(225945, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: 225945,
+            local_amount: from_minor(225945, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("BPF0021628".to_string()),
             description: Some("BPF0021628".to_string()),
             category_split: None,
+            pot: None,
+            sheet: None,
         };
 
         // Act
@@ -216,13 +243,15 @@ mod tests {
             payment_type: "Bacs (Direct Credit)".to_string(),
             name: "Citibank".to_string(),
             category: "Income".to_string(),
-            amount: 27956,
+            amount: from_minor(27956, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: 27956,
+            local_amount: from_minor(27956, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("AIRBNB PAYMENTS UK".to_string()),
             description: Some("AIRBNB PAYMENTS UK".to_string()),
             category_split: None,
+            pot: None,
+            sheet: None,
         };
 
         // Act
@@ -247,13 +276,15 @@ mod tests {
             payment_type: "Faster payment".to_string(),
             name: "Richard Lyon".to_string(),
             category: "Savings".to_string(),
-            amount: -10000,
+            amount: from_minor(-10000, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: -10000,
+            local_amount: from_minor(-10000, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("???".to_string()),
             description: Some("Richard Lyon".to_string()),
             category_split: None,
+            pot: None,
+            sheet: None,
         };
 
         // Act
@@ -264,7 +295,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore = "fix beancount fixture"]
     fn should_classify_transfer_opening_balance() {
         // Arrange
         let (asset_accounts, income_accounts) = get_test_accounts();
@@ -274,13 +304,15 @@ mod tests {
             payment_type: "Faster payment".to_string(),
             name: "Richard Lyon".to_string(),
             category: "Transfers".to_string(),
-            amount: 450087,
+            amount: from_minor(450087, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: 450087,
+            local_amount: from_minor(450087, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("Transfer in from Starling".to_string()),
             description: Some("Monzo-BHKTM".to_string()),
             category_split: None,
+            pot: None,
+            sheet: None,
         };
 
         // Act
@@ -290,6 +322,35 @@ mod tests {
         assert_eq!(classification, Some(Classification::TransferOpeningBalance));
     }
 
+    #[test]
+    fn should_give_review_reason_for_uncertain_classifications() {
+        let tx = |category: &str| GoogleTransaction {
+            name: "Richard Lyon".to_string(),
+            category: category.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            review_reason(Some(&Classification::TransferOpeningBalance), &tx("Transfers")),
+            Some("Transfer with \"Richard Lyon\", which isn't a configured asset account, was posted as an opening balance".to_string())
+        );
+        assert!(
+            review_reason(Some(&Classification::TransferOpeningBalance), &tx("Income")).is_some()
+        );
+        assert!(review_reason(None, &tx("General")).is_some());
+        assert_eq!(
+            review_reason(None, &tx("Groceries")),
+            Some(
+                "Category \"Groceries\" wasn't classified, so it was posted as spending"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            review_reason(Some(&Classification::Savings), &tx("Savings")),
+            None
+        );
+    }
+
     #[test]
     fn should_classify_transfer_pot() {
         // Arrange
@@ -300,13 +361,15 @@ mod tests {
             payment_type: "Pot transfer".to_string(),
             name: "Essential Fixed Pot".to_string(),
             category: "Transfers".to_string(),
-            amount: -10000,
+            amount: from_minor(-10000, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: -10000,
+            local_amount: from_minor(-10000, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("To fund pot".to_string()),
             description: None,
             category_split: None,
+            pot: Some("Essential Fixed Pot".to_string()),
+            sheet: None,
         };
 
         // Act
@@ -326,13 +389,15 @@ mod tests {
             payment_type: "Faster payment".to_string(),
             name: "NSI Premium Bonds".to_string(),
             category: "Transfers".to_string(),
-            amount: -10000,
+            amount: from_minor(-10000, "GBP"),
             currency: "GBP".to_string(),
-            local_amount: -10000,
+            local_amount: from_minor(-10000, "GBP"),
             local_currency: "GBP".to_string(),
             notes: Some("520344086".to_string()),
             description: Some("520344086".to_string()),
             category_split: None,
+            pot: None,
+            sheet: None,
         };
 
         // Act
@@ -347,11 +412,32 @@ mod tests {
     }
 
     fn get_test_accounts() -> (Vec<BeancountAccount>, Vec<BeancountAccount>) {
-        let data_dir = PathBuf::from("/Users/richardlyon/dev/rust-monzo-beancount/data");
-        let bc = Beancount::with_data_dir(data_dir).expect("Failed to create Beancount instance");
-        let asset_accounts = bc.user_settings.assets.clone().unwrap();
-        let income_accounts = bc.user_settings.income.clone().unwrap();
+        let asset_accounts = vec![
+            test_account(AccountType::Assets, "Monzo", "Personal"),
+            test_account(AccountType::Assets, "Monzo", "Business"),
+            test_account(AccountType::Assets, "NSI", "NSI Premium Bonds"),
+        ];
+        let income_accounts = vec![
+            test_account(AccountType::Income, "Monzo", "Personal"),
+            test_account(AccountType::Income, "BP", "Bp Pension Trustee"),
+            test_account(AccountType::Income, "Airbnb", "Citibank"),
+        ];
 
         (asset_accounts, income_accounts)
     }
+
+    fn test_account(
+        account_type: AccountType,
+        institution: &str,
+        account: &str,
+    ) -> BeancountAccount {
+        BeancountAccount {
+            account_type,
+            country: "GBP".to_string(),
+            institution: institution.to_string(),
+            account: account.to_string(),
+            sub_account: None,
+            name: None,
+        }
+    }
 }
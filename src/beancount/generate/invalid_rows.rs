@@ -0,0 +1,131 @@
+//! Reports the sheet and export rows left out of generation because they couldn't be parsed.
+
+use std::{fs, path::Path};
+
+use csv::WriterBuilder;
+
+use crate::beancount::source::InvalidRow;
+use crate::beancount::user_settings::InvalidRowPolicy;
+use crate::error::AppError as Error;
+
+/// Print a summary of `invalid_rows` and, when quarantining, write them to `quarantine_file`.
+///
+/// The quarantine file is replaced on each run so that it only holds the rows from the latest
+/// fetch, and removed once every row parses.
+pub(crate) fn report_invalid_rows(
+    invalid_rows: &[InvalidRow],
+    policy: InvalidRowPolicy,
+    quarantine_file: &Path,
+) -> Result<(), Error> {
+    if policy == InvalidRowPolicy::Quarantine {
+        if invalid_rows.is_empty() {
+            if quarantine_file.exists() {
+                fs::remove_file(quarantine_file)?;
+            }
+        } else {
+            write_quarantine_file(invalid_rows, quarantine_file)?;
+        }
+    }
+
+    if invalid_rows.is_empty() {
+        return Ok(());
+    }
+
+    eprintln!(
+        "Skipped {} row(s) that couldn't be parsed or posted:",
+        invalid_rows.len()
+    );
+    for row in invalid_rows {
+        eprintln!("  {}", row.error());
+    }
+    if policy == InvalidRowPolicy::Quarantine {
+        eprintln!("The rows were written to {}", quarantine_file.display());
+    }
+
+    Ok(())
+}
+
+// one record per row: where it came from and why it failed, followed by its cells
+fn write_quarantine_file(invalid_rows: &[InvalidRow], quarantine_file: &Path) -> Result<(), Error> {
+    let csv_error =
+        |e: csv::Error| Error::ApplicationError(format!("{}: {}", quarantine_file.display(), e));
+
+    let mut writer = WriterBuilder::new()
+        .flexible(true)
+        .from_path(quarantine_file)
+        .map_err(csv_error)?;
+
+    writer
+        .write_record(["sheet", "row", "column", "error", "values"])
+        .map_err(csv_error)?;
+
+    for row in invalid_rows {
+        let mut record = vec![
+            row.sheet.clone(),
+            row.row.map(|row| row.to_string()).unwrap_or_default(),
+            row.column.clone(),
+            row.message.clone(),
+        ];
+        record.extend(row.values.iter().cloned());
+        writer.write_record(&record).map_err(csv_error)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    fn invalid_row() -> InvalidRow {
+        InvalidRow {
+            sheet: "Personal".to_string(),
+            row: Some(3),
+            column: "Amount".to_string(),
+            message: "invalid amount \"-3.x\"".to_string(),
+            values: vec!["tx_0002".to_string(), "03/06/2024".to_string()],
+        }
+    }
+
+    #[test]
+    fn should_quarantine_invalid_rows() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let quarantine_file = dir.child("quarantine.csv");
+
+        // Act
+        report_invalid_rows(
+            &[invalid_row()],
+            InvalidRowPolicy::Quarantine,
+            &quarantine_file,
+        )
+        .unwrap();
+
+        // Assert
+        let contents = fs::read_to_string(&quarantine_file).unwrap();
+        assert_eq!(
+            contents,
+            "sheet,row,column,error,values\nPersonal,3,Amount,\"invalid amount \"\"-3.x\"\"\",tx_0002,03/06/2024\n"
+        );
+    }
+
+    #[test]
+    fn should_remove_quarantine_file_once_rows_parse() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let quarantine_file = dir.child("quarantine.csv");
+        fs::write(&quarantine_file, "stale").unwrap();
+
+        // Act
+        report_invalid_rows(&[], InvalidRowPolicy::Quarantine, &quarantine_file).unwrap();
+
+        // Assert
+        assert!(!quarantine_file.exists());
+    }
+}
@@ -1,10 +1,15 @@
 //! Processes inputs and generates a set of beancount accounts.
 //!
 
+pub(crate) mod balance_directives;
 pub(crate) mod classifier;
-pub(crate) mod google_sheet_directives;
+pub(crate) mod invalid_rows;
 pub(crate) mod open_directives;
+pub(crate) mod pot_directives;
+pub(crate) mod price_directives;
+pub(crate) mod transaction_directives;
 
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::{fs::File, io::Write};
@@ -14,41 +19,173 @@ use config::Case;
 use convert_case::Casing;
 use rusty_money::{iso, Money};
 
-use crate::beancount::generate::google_sheet_directives::google_sheet_directives;
 use crate::beancount::google::transactions::Transaction as GoogleTransaction;
 use crate::error::AppError as Error;
 
-use super::google::GoogleSheetAccount;
+use super::google::GoogleSheet;
+use super::monzo::MonzoApi;
+use super::monzo_csv::MonzoCsv;
+use super::parser::parse_file;
+use super::source::{InvalidRow, SourceAccount, TransactionSource, POT_TRANSFER};
+use super::store::TransactionStore;
+use super::user_settings::{InvalidRowPolicy, PriceAnnotation, UserSettings};
 use super::{
     account::{Account as BeancountAccount, AccountType},
     directive::Directive,
-    transaction::{Posting, Postings, Transaction as BeancountTransaction},
+    transaction::{Flag, Metadata, Posting, TotalPrice, Transaction as BeancountTransaction},
     Beancount,
 };
 
+use balance_directives::balance_directives;
+use invalid_rows::report_invalid_rows;
 use open_directives::open_directives;
+use pot_directives::pot_directives;
+use price_directives::price_directives;
+use transaction_directives::transaction_directives;
+
+/// The number of sheets downloaded at the same time unless configured otherwise.
+const DEFAULT_FETCH_CONCURRENCY: usize = 4;
 
 impl Beancount {
     /// Process the input and produce a set of Beancount accounts
     pub async fn generate(&self) -> Result<(), Error> {
+        let sources = self.transaction_sources().await?;
+
+        let invalid_rows: Vec<InvalidRow> = sources
+            .iter()
+            .flat_map(|source| source.invalid_rows().to_vec())
+            .collect();
+        let policy = self.user_settings.invalid_rows;
+        if let (InvalidRowPolicy::Fail, Some(row)) = (policy, invalid_rows.first()) {
+            return Err(row.error());
+        }
+
+        let mut store = TransactionStore::open(&self.data_file_paths.store_file)?;
+        for source in &sources {
+            store.save(source.as_ref())?;
+        }
+
+        let unposted = self.generate_from_store(&store).await?;
+        let invalid_rows = [invalid_rows, unposted].concat();
+
+        report_invalid_rows(&invalid_rows, policy, &self.data_file_paths.quarantine_file)
+    }
+
+    /// Produce a set of Beancount accounts from the transaction store without fetching.
+    pub async fn generate_offline(&self) -> Result<(), Error> {
+        let store = TransactionStore::open(&self.data_file_paths.store_file)?;
+
+        let unposted = self.generate_from_store(&store).await?;
+
+        // the quarantine file keeps the rows of the latest fetch
+        report_invalid_rows(
+            &unposted,
+            InvalidRowPolicy::Skip,
+            &self.data_file_paths.quarantine_file,
+        )
+    }
+
+    async fn generate_from_store(
+        &self,
+        store: &TransactionStore,
+    ) -> Result<Vec<InvalidRow>, Error> {
+        let sources: Vec<Box<dyn TransactionSource>> = vec![Box::new(store.load()?)];
+
+        self.generate_from_sources(&sources).await
+    }
+
+    /// Produce a set of Beancount accounts from the transactions in `sources`, returning the
+    /// transactions that couldn't be posted.
+    ///
+    /// Under the `fail` invalid row policy, the first transaction that can't be posted stops
+    /// generation before the main file is written.
+    pub async fn generate_from_sources(
+        &self,
+        sources: &[Box<dyn TransactionSource>],
+    ) -> Result<Vec<InvalidRow>, Error> {
         let option_directives = option_directives();
 
-        let include_directives = include_directives(self.data_file_paths.include_dir.clone())?;
+        let (include_directives, included_opens) =
+            include_directives(self.data_file_paths.include_dir.clone())?;
 
-        let open_directives = open_directives(self.user_settings.clone()).await?;
+        let open_directives = open_directives(self.user_settings.clone(), sources).await?;
 
-        let transaction_directives = match &self.user_settings.googlesheet_accounts {
-            Some(a) => google_sheet_directives(self, a).await?,
-            None => vec![],
-        };
+        let pot_directives = pot_directives(sources);
+
+        let (transaction_directives, unposted) = transaction_directives(self, sources)?;
+        if let (InvalidRowPolicy::Fail, Some(row)) =
+            (self.user_settings.invalid_rows, unposted.first())
+        {
+            return Err(row.error());
+        }
+
+        let price_directives = price_directives(sources);
+
+        let balance_directives = balance_directives(&self.data_file_paths.balances_file)?;
+
+        let mut directives: Vec<Directive> = [
+            option_directives,
+            include_directives,
+            open_directives,
+            pot_directives,
+            transaction_directives,
+            price_directives,
+            balance_directives,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        name_accounts(&mut directives, &self.user_settings)?;
+
+        // an account may only be opened once, so those opened by included files are left to them
+        directives.retain(|directive| match directive {
+            Directive::Open(_, account, _, _) => !included_opens.contains(&account.to_string()),
+            _ => true,
+        });
 
         let mut file = File::create(self.data_file_paths.main_file.clone())?;
-        write_directives(&mut file, option_directives)?;
-        write_directives(&mut file, include_directives)?;
-        write_directives(&mut file, open_directives)?;
-        write_directives(&mut file, transaction_directives)?;
+        write_directives(&mut file, directives)?;
 
-        Ok(())
+        Ok(unposted)
+    }
+
+    /// Construct the transaction sources configured in the user settings.
+    pub(crate) async fn transaction_sources(
+        &self,
+    ) -> Result<Vec<Box<dyn TransactionSource>>, Error> {
+        let mut sources: Vec<Box<dyn TransactionSource>> = Vec::new();
+
+        if let Some(accounts) = &self.user_settings.googlesheet_accounts {
+            let concurrency = self
+                .user_settings
+                .fetch_concurrency
+                .unwrap_or(DEFAULT_FETCH_CONCURRENCY);
+            for sheet in GoogleSheet::fetch_all(&self.google_api, accounts, concurrency).await? {
+                sources.push(Box::new(sheet));
+            }
+        }
+
+        if let Some(accounts) = &self.user_settings.monzo_csv_accounts {
+            for account in accounts {
+                sources.push(Box::new(MonzoCsv::new(
+                    account.clone(),
+                    &self.data_file_paths.data_dir,
+                )?));
+            }
+        }
+
+        if let Some(settings) = &self.user_settings.monzo_api {
+            sources.push(Box::new(
+                MonzoApi::new(
+                    settings.clone(),
+                    &self.monzo_auth,
+                    self.user_settings.start_date,
+                )
+                .await?,
+            ));
+        }
+
+        Ok(sources)
     }
 }
 
@@ -59,8 +196,11 @@ fn option_directives() -> Vec<Directive> {
     ]
 }
 
-fn include_directives(include_dir: PathBuf) -> Result<Vec<Directive>, Error> {
+/// An include directive for each Beancount file in `include_dir`, and the names of the accounts
+/// the files open.
+fn include_directives(include_dir: PathBuf) -> Result<(Vec<Directive>, HashSet<String>), Error> {
     let mut directives: Vec<Directive> = vec![];
+    let mut opened: HashSet<String> = HashSet::new();
     let mut beanfiles = Vec::new();
 
     let files = fs::read_dir(include_dir)?;
@@ -78,12 +218,19 @@ fn include_directives(include_dir: PathBuf) -> Result<Vec<Directive>, Error> {
     }
 
     for beanfile in beanfiles {
+        let included = parse_file(&beanfile)
+            .map_err(|e| Error::ApplicationError(format!("{}: {}", beanfile.display(), e)))?;
+        opened.extend(included.iter().filter_map(|directive| match directive {
+            Directive::Open(_, account, _, _) => Some(account.to_string()),
+            _ => None,
+        }));
+
         let subpath = extract_last_two_components(&beanfile)?;
         let include_path = &subpath.to_string_lossy().to_string()[1..];
         directives.push(Directive::Include(include_path.to_string()));
     }
 
-    Ok(directives)
+    Ok((directives, opened))
 }
 
 fn extract_last_two_components(path: &Path) -> Result<PathBuf, Error> {
@@ -104,6 +251,82 @@ fn extract_last_two_components(path: &Path) -> Result<PathBuf, Error> {
     }
 }
 
+/// Name the accounts in `directives` as configured in `user_settings`, by its transliterations and
+/// account name templates.
+///
+/// Templates can give accounts from different sources the same name, such as one expense tree
+/// for every bank account, so only the first open directive for each name is kept, allowing the
+/// currencies of the others.
+pub(crate) fn name_accounts(
+    directives: &mut Vec<Directive>,
+    user_settings: &UserSettings,
+) -> Result<(), Error> {
+    for directive in directives.iter_mut() {
+        for account in directive.accounts_mut() {
+            // accounts named explicitly, or as they're written in the settings, keep their names
+            if account.name.is_some() || user_settings.is_configured(account) {
+                continue;
+            }
+
+            account.transliterate(&user_settings.transliterations);
+            let Some(template) = user_settings.account_names.template(&account.account_type) else {
+                continue;
+            };
+            let uncategorised = account
+                .sub_account
+                .as_deref()
+                .unwrap_or_default()
+                .is_empty();
+            match account.apply_template(template) {
+                // a template may need the category, so an account without one keeps its default name
+                Err(_) if uncategorised => {}
+                result => {
+                    result.map_err(|e| Error::AccountTemplateError(template.to_string(), e))?
+                }
+            }
+        }
+    }
+
+    let mut opened: HashMap<String, usize> = HashMap::new();
+    let mut duplicates: HashSet<usize> = HashSet::new();
+
+    for i in 0..directives.len() {
+        let (date, name, currencies) = match &directives[i] {
+            Directive::Open(date, account, other_currencies, _) => (
+                *date,
+                account.to_string(),
+                std::iter::once(account.country.clone())
+                    .chain(other_currencies.iter().cloned())
+                    .collect::<Vec<String>>(),
+            ),
+            _ => continue,
+        };
+
+        let Some(&first) = opened.get(&name) else {
+            opened.insert(name, i);
+            continue;
+        };
+        if let Directive::Open(opened_on, account, other_currencies, _) = &mut directives[first] {
+            *opened_on = (*opened_on).min(date);
+            for currency in currencies {
+                if currency != account.country && !other_currencies.contains(&currency) {
+                    other_currencies.push(currency);
+                }
+            }
+        }
+        duplicates.insert(i);
+    }
+
+    let mut index = 0;
+    directives.retain(|_| {
+        let keep = !duplicates.contains(&index);
+        index += 1;
+        keep
+    });
+
+    Ok(())
+}
+
 fn write_directives(file: &mut File, directives: Vec<Directive>) -> Result<(), Error> {
     for d in directives {
         file.write_all(d.to_formatted_string().as_bytes())?;
@@ -113,9 +336,9 @@ fn write_directives(file: &mut File, directives: Vec<Directive>) -> Result<(), E
 }
 
 fn prepare_to_posting(
-    asset_accounts: &Vec<BeancountAccount>,
-    income_accounts: &Vec<BeancountAccount>,
-    account: &GoogleSheetAccount,
+    asset_accounts: &[BeancountAccount],
+    income_accounts: &[BeancountAccount],
+    account: &SourceAccount,
     tx: &GoogleTransaction,
 ) -> Result<Posting, Error> {
     let mut account = BeancountAccount {
@@ -124,9 +347,9 @@ fn prepare_to_posting(
         institution: account.institution.clone(),
         account: account.name.clone().to_case(Case::Pascal),
         sub_account: Some(tx.category.clone().to_case(Case::Pascal)),
-        transaction_id: None,
+        name: None,
     };
-    let mut amount = -tx.amount as f64;
+    let mut amount = -tx.amount;
 
     #[allow(clippy::assigning_clones)] // TODO: Remove this
     if let Some(classification) = classify_transaction(asset_accounts, income_accounts, tx)? {
@@ -134,13 +357,13 @@ fn prepare_to_posting(
             Classification::IncomeGeneral => {
                 // OK
                 account.account_type = AccountType::Assets;
-                account.sub_account = None;
-                amount = tx.amount as f64;
+                account.sub_account = paid_from_pot(tx);
+                amount = tx.amount;
             }
             Classification::IncomeAccount(_institution_account) => {
                 account.account_type = AccountType::Assets;
-                account.sub_account = None;
-                amount = tx.amount as f64;
+                account.sub_account = paid_from_pot(tx);
+                amount = tx.amount;
             }
             Classification::Savings => {
                 account.account_type = AccountType::Assets;
@@ -149,18 +372,15 @@ fn prepare_to_posting(
             Classification::TransferOpeningBalance => {
                 account.account_type = AccountType::Assets;
                 account.sub_account = None;
-                amount = tx.amount as f64;
+                amount = tx.amount;
             }
             Classification::TransferPot => {
                 account.account_type = AccountType::Assets;
-                account.sub_account = Some(tx.name.clone());
+                account.sub_account = tx.pot.clone().or_else(|| Some(tx.name.clone()));
             }
             Classification::TransferAsset(asset_account) => {
-                // OK
-                account.account_type = AccountType::Assets;
-                account.institution = asset_account.institution;
-                account.account = asset_account.account.clone();
-                account.sub_account = None;
+                // the configured account, so that it's posted to under the name it's opened with
+                account = asset_account;
             }
         }
     }
@@ -174,27 +394,28 @@ fn prepare_to_posting(
 
     Ok(Posting {
         account,
-        amount,
+        amount: Some(amount),
         currency: tx.currency.to_string(),
-        description: tx.description.clone(),
+        price: None,
+        metadata: Metadata::new(),
     })
 }
 
 fn prepare_from_posting(
-    asset_accounts: &Vec<BeancountAccount>,
-    income_accounts: &Vec<BeancountAccount>,
-    account: &GoogleSheetAccount,
+    asset_accounts: &[BeancountAccount],
+    income_accounts: &[BeancountAccount],
+    account: &SourceAccount,
     tx: &GoogleTransaction,
 ) -> Result<Posting, Error> {
-    let mut amount = tx.amount as f64;
+    let mut amount = tx.amount;
 
     let mut account = BeancountAccount {
         account_type: AccountType::Assets,
         country: account.country.clone(),
         institution: account.institution.clone(),
         account: account.name.clone().to_case(Case::Pascal),
-        sub_account: None,
-        transaction_id: Some(tx.id.clone()),
+        sub_account: paid_from_pot(tx),
+        name: None,
     };
 
     #[allow(clippy::assigning_clones)] // TODO: Remove this
@@ -202,13 +423,15 @@ fn prepare_from_posting(
         match classification {
             Classification::IncomeGeneral => {
                 account.account_type = AccountType::Income;
-                amount = -tx.amount as f64;
+                account.sub_account = None;
+                amount = -tx.amount;
             }
             Classification::IncomeAccount(income_account) => {
                 account.account_type = AccountType::Income;
                 account.institution = income_account.institution;
                 account.account = tx.name.clone();
-                amount = -tx.amount as f64;
+                account.sub_account = None;
+                amount = -tx.amount;
             }
             Classification::Savings => {
                 account.account_type = AccountType::Assets;
@@ -216,7 +439,8 @@ fn prepare_from_posting(
             Classification::TransferOpeningBalance => {
                 account.account_type = AccountType::Equity;
                 account.account = "OpeningBalances".to_string();
-                amount = -tx.amount as f64;
+                account.sub_account = None;
+                amount = -tx.amount;
             }
             Classification::TransferPot => {
                 account.account_type = AccountType::Assets;
@@ -234,42 +458,687 @@ fn prepare_from_posting(
 
     Ok(Posting {
         account,
-        amount,
+        amount: Some(amount),
         currency: tx.currency.to_string(),
-        description: None,
+        price: None,
+        metadata: Metadata::new(),
     })
 }
 
-fn prepare_transaction(postings: &Postings, tx: &GoogleTransaction) -> BeancountTransaction {
-    let comment = prepare_transaction_comment(tx);
+/// One expense posting per category of a split transaction, in place of `to_posting`.
+///
+/// Transactions that aren't split, or that aren't classified as expenses, keep `to_posting`.
+fn prepare_split_postings(
+    to_posting: Posting,
+    tx: &GoogleTransaction,
+) -> Result<Vec<Posting>, Error> {
+    let splits = match &tx.category_split {
+        Some(splits) if to_posting.account.account_type == AccountType::Expenses => splits,
+        _ => return Ok(vec![to_posting]),
+    };
+
+    tx.check_category_split()?;
+
+    let postings = splits
+        .iter()
+        .map(|split| Posting {
+            account: BeancountAccount {
+                sub_account: Some(split.category.clone().to_case(Case::Pascal)),
+                ..to_posting.account.clone()
+            },
+            amount: Some(-split.amount),
+            currency: to_posting.currency.clone(),
+            price: None,
+            metadata: to_posting.metadata.clone(),
+        })
+        .collect();
+
+    Ok(postings)
+}
+
+/// Record a transaction made abroad in its local currency on the expense posting, with an `@@`
+/// total price on the posting `annotation` selects so that the transaction still balances.
+///
+/// Other transactions, and split or non-expense ones, keep their postings in the account currency.
+fn prepare_foreign_postings(
+    to_posting: Posting,
+    from_posting: Posting,
+    tx: &GoogleTransaction,
+    annotation: PriceAnnotation,
+) -> (Posting, Posting) {
+    if !tx.is_foreign()
+        || tx.category_split.is_some()
+        || to_posting.account.account_type != AccountType::Expenses
+    {
+        return (to_posting, from_posting);
+    }
+
+    let amount = to_posting.amount.unwrap_or_default();
+    let local_amount = if amount.is_sign_negative() {
+        -tx.local_amount.abs()
+    } else {
+        tx.local_amount.abs()
+    };
+
+    let local_posting = Posting {
+        amount: Some(local_amount),
+        currency: tx.local_currency.clone(),
+        ..to_posting.clone()
+    };
+
+    match annotation {
+        PriceAnnotation::Local => (
+            Posting {
+                price: Some(TotalPrice {
+                    amount: amount.abs(),
+                    currency: to_posting.currency,
+                }),
+                ..local_posting
+            },
+            from_posting,
+        ),
+        PriceAnnotation::Account => (
+            local_posting,
+            Posting {
+                price: Some(TotalPrice {
+                    amount: local_amount.abs(),
+                    currency: tx.local_currency.clone(),
+                }),
+                ..from_posting
+            },
+        ),
+    }
+}
+
+/// The asset account for `pot_name`, held within `account`.
+pub(crate) fn pot_account(account: &SourceAccount, pot_name: &str) -> BeancountAccount {
+    BeancountAccount {
+        account_type: AccountType::Assets,
+        country: account.country.clone(),
+        institution: account.institution.clone(),
+        account: account.name.clone().to_case(Case::Pascal),
+        sub_account: Some(pot_name.to_string()),
+        name: None,
+    }
+}
+
+// The pot a transaction was paid from or into, other than by a pot transfer.
+fn paid_from_pot(tx: &GoogleTransaction) -> Option<String> {
+    match tx.payment_type.as_str() {
+        POT_TRANSFER => None,
+        _ => tx.pot.clone(),
+    }
+}
+
+/// The transaction for `tx`, flagged `!` with a `review` note when `review` gives a reason to
+/// doubt its classification.
+fn prepare_transaction(
+    postings: Vec<Posting>,
+    tx: &GoogleTransaction,
+    review: Option<String>,
+) -> BeancountTransaction {
+    // the local amount is only noted when the postings don't record it
+    let comment = if postings.iter().any(|posting| posting.price.is_some()) {
+        None
+    } else {
+        prepare_transaction_comment(tx)
+    };
     let date = tx.date;
-    let notes = prepare_transaction_notes(tx);
+    let payee = Some(tx.name.clone()).filter(|name| !name.is_empty());
+    let (narration, tags) = prepare_transaction_narration(tx);
+    let mut metadata = prepare_transaction_metadata(tx);
+    let flag = match review {
+        Some(reason) => {
+            metadata.insert("review".to_string(), reason);
+            Flag::Incomplete
+        }
+        None => Flag::Complete,
+    };
 
     BeancountTransaction {
         comment,
         date,
-        notes,
-        postings: postings.clone(),
+        flag,
+        payee,
+        narration,
+        tags,
+        links: BTreeSet::new(),
+        metadata,
+        postings,
+    }
+}
+
+// Metadata to trace the transaction back to the bank and the sheet it came from.
+fn prepare_transaction_metadata(tx: &GoogleTransaction) -> Metadata {
+    let mut metadata = Metadata::from([("monzo-id".to_string(), tx.id.clone())]);
+
+    if !tx.payment_type.is_empty() {
+        metadata.insert("payment-type".to_string(), tx.payment_type.clone());
+    }
+    if let Some(sheet) = &tx.sheet {
+        metadata.insert("sheet".to_string(), sheet.clone());
     }
+    if let Some(description) = &tx.description {
+        metadata.insert("description".to_string(), description.clone());
+    }
+
+    metadata
 }
 
+// The amount in the local currency, when it was spent abroad.
 fn prepare_transaction_comment(tx: &GoogleTransaction) -> Option<String> {
-    let amount = prepare_amount(tx);
-    let notes = tx.notes.clone().unwrap_or_default();
+    Some(prepare_amount(tx)).filter(|amount| !amount.is_empty())
+}
 
-    Some(format!("{notes} {amount}"))
+// The user's notes, or the bank's description if there are none, and the `#hashtags` taken
+// out of the notes as tags.
+fn prepare_transaction_narration(tx: &GoogleTransaction) -> (String, BTreeSet<String>) {
+    let (notes, tags) = extract_tags(tx.notes.as_deref().unwrap_or_default());
+
+    let narration = Some(notes)
+        .filter(|notes| !notes.is_empty())
+        .or_else(|| tx.description.clone())
+        .unwrap_or_default();
+
+    (narration, tags)
 }
 
-fn prepare_transaction_notes(tx: &GoogleTransaction) -> String {
-    tx.name.clone()
+// Split `#hashtags` out of `text`, e.g. `"Dinner #trip-paris"` -> `"Dinner"`, `{"trip-paris"}`.
+// Trailing punctuation isn't part of a tag, and words that aren't valid Beancount tags are left
+// in the text.
+fn extract_tags(text: &str) -> (String, BTreeSet<String>) {
+    let mut words: Vec<&str> = Vec::new();
+    let mut tags = BTreeSet::new();
+
+    for word in text.split_whitespace() {
+        let tag = word
+            .strip_prefix('#')
+            .map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric()))
+            .filter(|tag| {
+                !tag.is_empty()
+                    && tag
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "-_/.".contains(c))
+            });
+
+        match tag {
+            Some(tag) => {
+                tags.insert(tag.to_string());
+            }
+            None => words.push(word),
+        }
+    }
+
+    (words.join(" "), tags)
 }
 
 fn prepare_amount(tx: &GoogleTransaction) -> String {
     if tx.currency == tx.local_currency {
         String::new()
     } else if let Some(iso_code) = iso::find(&tx.local_currency) {
-        format!("{}", Money::from_minor(tx.local_amount, iso_code))
+        format!("{}", Money::from_decimal(tx.local_amount, iso_code))
     } else {
         format!("{} {}", tx.local_amount, tx.local_currency)
     }
 }
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use chrono::NaiveDate;
+    use temp_dir::TempDir;
+    use wiremock::MockServer;
+
+    use crate::beancount::google::tests::{mock_api, mock_server, SHEET_ID};
+    use crate::beancount::google::transactions::CategorySplit;
+    use crate::beancount::money::Decimal;
+
+    use super::*;
+
+    /// A data directory configured to read the stand-in sheet served by `server`.
+    pub(crate) fn test_beancount(dir: &TempDir, server: &MockServer) -> Beancount {
+        fs::create_dir(dir.child("include")).unwrap();
+        fs::write(
+            dir.child("beancount.yaml"),
+            format!(
+                r#"start_date: "2024-01-01"
+googlesheet_accounts:
+  - country: "GBP"
+    institution: "Monzo"
+    name: "Personal"
+    sheet_name: "Personal Account Transactions"
+    sheet_id: "{SHEET_ID}"
+assets:
+  - account_type: Assets
+    country: GBP
+    institution: Monzo
+    account: Personal
+liabilities: []
+income: []
+expenses: []
+"#
+            ),
+        )
+        .unwrap();
+
+        Beancount::with_data_dir(dir.path().to_path_buf())
+            .unwrap()
+            .with_google_api(mock_api(server))
+    }
+
+    #[tokio::test]
+    async fn should_generate_from_google_sheet() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let beancount = test_beancount(&dir, &server);
+
+        // Act
+        beancount.generate().await.unwrap();
+
+        // Assert
+        let main_file = fs::read_to_string(&beancount.data_file_paths.main_file).unwrap();
+        assert!(main_file.contains("open Expenses:GBP:Monzo:Personal:Groceries"));
+        assert!(main_file.contains("open Expenses:GBP:Monzo:Personal:EatingOut"));
+        assert!(main_file.contains("2024-06-02 ! \"Tesco\""));
+        assert!(main_file.contains("2024-06-28 * \"Acme Ltd\" \"June salary\"\n"));
+        assert!(main_file.contains("  monzo-id: \"tx_0001\"\n"));
+        assert!(main_file.contains("  sheet: \"Personal Account Transactions\"\n"));
+        assert!(
+            main_file.contains("Assets:GBP:Monzo:Personal                             2500.00 GBP")
+        );
+        assert!(beancount.data_file_paths.store_file.exists());
+    }
+
+    #[tokio::test]
+    async fn should_generate_offline_from_store() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let beancount = test_beancount(&dir, &server);
+        beancount.generate().await.unwrap();
+        let online = fs::read_to_string(&beancount.data_file_paths.main_file).unwrap();
+        drop(server);
+
+        // Act
+        beancount.generate_offline().await.unwrap();
+
+        // Assert
+        let offline = fs::read_to_string(&beancount.data_file_paths.main_file).unwrap();
+        assert_eq!(offline, online);
+    }
+
+    #[tokio::test]
+    async fn should_leave_accounts_opened_by_includes_to_them() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let beancount = test_beancount(&dir, &server);
+        fs::write(
+            dir.child("include").join("groceries.beancount"),
+            "2020-01-01 open Expenses:GBP:Monzo:Personal:Groceries GBP\n",
+        )
+        .unwrap();
+
+        // Act
+        beancount.generate().await.unwrap();
+
+        // Assert
+        let main_file = fs::read_to_string(&beancount.data_file_paths.main_file).unwrap();
+        assert!(main_file.contains("include \"include/groceries.beancount\""));
+        assert!(!main_file.contains("open Expenses:GBP:Monzo:Personal:Groceries"));
+        assert!(main_file.contains("open Expenses:GBP:Monzo:Personal:EatingOut"));
+    }
+
+    #[tokio::test]
+    async fn should_report_include_that_cant_be_parsed() {
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let beancount = test_beancount(&dir, &server);
+        fs::write(
+            dir.child("include").join("broken.beancount"),
+            "not beancount\n",
+        )
+        .unwrap();
+
+        let result = beancount.generate().await;
+
+        assert!(
+            matches!(result, Err(Error::ApplicationError(message)) if message.contains("broken.beancount"))
+        );
+    }
+
+    #[test]
+    fn should_post_each_category_of_a_split() {
+        // Arrange
+        let source_account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let split = |category: &str, amount: i64| CategorySplit {
+            category: category.to_string(),
+            amount: Decimal::new(amount, 2),
+        };
+        let tx = GoogleTransaction {
+            id: "tx_0001".to_string(),
+            category: "Groceries".to_string(),
+            amount: Decimal::new(-1000, 2),
+            currency: "GBP".to_string(),
+            category_split: Some(vec![split("Groceries", -800), split("Household", -200)]),
+            ..Default::default()
+        };
+        let to_posting = prepare_to_posting(&[], &[], &source_account, &tx).unwrap();
+
+        // Act
+        let postings = prepare_split_postings(to_posting, &tx).unwrap();
+
+        // Assert
+        assert_eq!(postings.len(), 2);
+        assert_eq!(
+            postings[1].account.to_string(),
+            "Expenses:GBP:Monzo:Personal:Household"
+        );
+        assert_eq!(postings[0].amount, Some(Decimal::new(800, 2)));
+        assert_eq!(postings[1].amount, Some(Decimal::new(200, 2)));
+    }
+
+    #[test]
+    fn should_reject_split_that_does_not_total() {
+        let tx = GoogleTransaction {
+            id: "tx_0001".to_string(),
+            amount: Decimal::new(-1200, 2),
+            category_split: Some(vec![CategorySplit {
+                category: "Groceries".to_string(),
+                amount: Decimal::new(-1000, 2),
+            }]),
+            ..Default::default()
+        };
+        let source_account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let to_posting = prepare_to_posting(&[], &[], &source_account, &tx).unwrap();
+
+        let result = prepare_split_postings(to_posting, &tx);
+
+        assert!(matches!(
+            result,
+            Err(Error::CategorySplitTotalError(_, _, _))
+        ));
+    }
+
+    #[test]
+    fn should_record_local_amount_of_foreign_spending() {
+        // Arrange
+        let source_account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let tx = GoogleTransaction {
+            id: "tx_0001".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 2).unwrap(),
+            description: Some("Diner".to_string()),
+            category: "Eating out".to_string(),
+            amount: Decimal::new(-80, 2),
+            currency: "GBP".to_string(),
+            local_amount: Decimal::new(-100, 2),
+            local_currency: "USD".to_string(),
+            ..Default::default()
+        };
+        let postings = |annotation: PriceAnnotation| {
+            let to_posting = prepare_to_posting(&[], &[], &source_account, &tx).unwrap();
+            let from_posting = prepare_from_posting(&[], &[], &source_account, &tx).unwrap();
+            let (to_posting, from_posting) =
+                prepare_foreign_postings(to_posting, from_posting, &tx, annotation);
+            prepare_transaction(vec![to_posting, from_posting], &tx, None).to_formatted_string()
+        };
+
+        // Act
+        let local = postings(PriceAnnotation::Local);
+        let account = postings(PriceAnnotation::Account);
+
+        // Assert
+        assert_eq!(
+            local,
+            r#"2024-06-02 * "Diner"
+  description: "Diner"
+  monzo-id: "tx_0001"
+  Expenses:GBP:Monzo:Personal:EatingOut                    1.00 USD @@ 0.80 GBP
+  Assets:GBP:Monzo:Personal                               -0.80 GBP
+"#
+        );
+        assert_eq!(
+            account,
+            r#"2024-06-02 * "Diner"
+  description: "Diner"
+  monzo-id: "tx_0001"
+  Expenses:GBP:Monzo:Personal:EatingOut                    1.00 USD
+  Assets:GBP:Monzo:Personal                               -0.80 GBP @@ 1.00 USD
+"#
+        );
+    }
+
+    #[test]
+    fn should_name_accounts_by_template() {
+        // Arrange
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let expense = |account: &str, category: &str| BeancountAccount {
+            account_type: AccountType::Expenses,
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            account: account.to_string(),
+            sub_account: Some(category.to_string()),
+            name: None,
+        };
+        let earlier = NaiveDate::from_ymd_opt(2023, 6, 1).unwrap();
+        let mut directives = vec![
+            Directive::Open(date, expense("Personal", "Eating out"), vec![], None),
+            Directive::Open(
+                earlier,
+                expense("Joint", "Eating out"),
+                vec!["USD".to_string()],
+                None,
+            ),
+            Directive::Open(date, expense("Joint", "Groceries"), vec![], None),
+        ];
+        let mut user_settings: UserSettings = serde_yaml::from_str(
+            r#"start_date: "2024-01-01"
+account_names:
+  expenses: "Expenses:{category}"
+"#,
+        )
+        .unwrap();
+        user_settings
+            .transliterations
+            .insert("Eating out".to_string(), "Restaurants".to_string());
+
+        // Act
+        name_accounts(&mut directives, &user_settings).unwrap();
+
+        // Assert
+        let rendered: String = directives.iter().map(|d| d.to_formatted_string()).collect();
+        assert_eq!(
+            rendered,
+            "2023-06-01 open Expenses:Restaurants                               GBP,USD\n\
+             2024-01-01 open Expenses:Groceries                                 GBP\n"
+        );
+    }
+
+    #[test]
+    fn should_keep_pots_apart_from_their_account() {
+        // Arrange
+        let opened = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let closed = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap();
+        let account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let main = BeancountAccount {
+            sub_account: None,
+            ..pot_account(&account, "")
+        };
+        let pot = pot_account(&account, "Car Tax");
+        let mut directives = vec![
+            Directive::Open(opened, main, vec![], None),
+            Directive::Open(opened, pot.clone(), vec![], None),
+            Directive::Close(closed, pot, None),
+        ];
+        let user_settings: UserSettings = serde_yaml::from_str(
+            r#"start_date: "2024-01-01"
+account_names:
+  assets: "Assets:{institution}:{account}:{sub_account}"
+"#,
+        )
+        .unwrap();
+
+        // Act
+        name_accounts(&mut directives, &user_settings).unwrap();
+
+        // Assert
+        let rendered: String = directives.iter().map(|d| d.to_formatted_string()).collect();
+        assert_eq!(
+            rendered,
+            "2024-01-01 open Assets:Monzo:Personal                              GBP\n\
+             2024-01-01 open Assets:Monzo:Personal:CarTax                       GBP\n\
+             2024-05-20 close Assets:Monzo:Personal:CarTax                      \n"
+        );
+    }
+
+    #[test]
+    fn should_transfer_to_named_asset_account() {
+        // Arrange
+        let asset_accounts: Vec<BeancountAccount> = vec!["Assets:Monzo:Joint".parse().unwrap()];
+        let joint = &asset_accounts[0];
+        let account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let tx = GoogleTransaction {
+            id: "tx_0001".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 2).unwrap(),
+            payment_type: "Faster payment".to_string(),
+            name: "Joint".to_string(),
+            category: "Transfers".to_string(),
+            amount: Decimal::new(-5000, 2),
+            currency: "GBP".to_string(),
+            ..Default::default()
+        };
+        let open = Directive::Open(tx.date, joint.clone(), vec![], None);
+
+        // Act
+        let posting = prepare_to_posting(&asset_accounts, &[], &account, &tx).unwrap();
+
+        // Assert
+        assert_eq!(&posting.account, joint);
+        let parsed = crate::beancount::parser::parse(&open.to_formatted_string()).unwrap();
+        assert!(
+            matches!(&parsed[..], [Directive::Open(_, opened, currencies, _)]
+            if opened.to_string() == posting.account.to_string() && currencies.is_empty())
+        );
+    }
+
+    #[test]
+    fn should_keep_named_and_uncategorised_accounts() {
+        // Arrange
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let expense = |sub_account: Option<&str>| BeancountAccount {
+            account_type: AccountType::Expenses,
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            account: "Personal".to_string(),
+            sub_account: sub_account.map(str::to_string),
+            name: None,
+        };
+        let named = BeancountAccount {
+            name: Some("Expenses:Holidays:Paris".to_string()),
+            ..expense(Some("Holidays"))
+        };
+        let mut directives = vec![
+            Directive::Open(date, expense(None), vec![], None),
+            Directive::Open(date, named, vec![], None),
+            Directive::Open(date, expense(Some("Rent")), vec![], None),
+            Directive::Open(date, expense(Some("Groceries")), vec![], None),
+        ];
+        let user_settings: UserSettings = serde_yaml::from_str(
+            r#"start_date: "2024-01-01"
+account_names:
+  expenses: "Expenses:{category}"
+expenses:
+  - Expenses:GBP:Monzo:Personal:Rent
+"#,
+        )
+        .unwrap();
+
+        // Act
+        name_accounts(&mut directives, &user_settings).unwrap();
+
+        // Assert
+        let rendered: String = directives.iter().map(|d| d.to_formatted_string()).collect();
+        assert_eq!(
+            rendered,
+            "2024-01-01 open Expenses:GBP:Monzo:Personal                        GBP\n\
+             2024-01-01 open Expenses:Holidays:Paris                            GBP\n\
+             2024-01-01 open Expenses:GBP:Monzo:Personal:Rent                   GBP\n\
+             2024-01-01 open Expenses:Groceries                                 GBP\n"
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_templated_names() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut directives = vec![Directive::Open(
+            date,
+            BeancountAccount {
+                account_type: AccountType::Expenses,
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                account: "Personal".to_string(),
+                sub_account: Some("Groceries".to_string()),
+                name: None,
+            },
+            vec![],
+            None,
+        )];
+        let user_settings: UserSettings = serde_yaml::from_str(
+            r#"start_date: "2024-01-01"
+account_names:
+  expenses: "Expenses:{category}_{account}"
+"#,
+        )
+        .unwrap();
+
+        let result = name_accounts(&mut directives, &user_settings);
+
+        assert!(matches!(result, Err(Error::AccountTemplateError(..))));
+    }
+
+    #[test]
+    fn should_take_hashtags_from_notes_as_tags() {
+        let tx = |notes: &str| GoogleTransaction {
+            notes: Some(notes.to_string()).filter(|n| !n.is_empty()),
+            description: Some("PRET A MANGER".to_string()),
+            ..Default::default()
+        };
+
+        let (narration, tags) = prepare_transaction_narration(&tx("Lunch #trip-paris, #work"));
+        assert_eq!(narration, "Lunch");
+        assert_eq!(
+            tags,
+            BTreeSet::from(["trip-paris".to_string(), "work".to_string()])
+        );
+
+        let (narration, tags) = prepare_transaction_narration(&tx("#trip-paris"));
+        assert_eq!(narration, "PRET A MANGER");
+        assert_eq!(tags, BTreeSet::from(["trip-paris".to_string()]));
+
+        let (narration, tags) = prepare_transaction_narration(&tx("Table # 4 #café"));
+        assert_eq!(narration, "Table # 4 #café");
+        assert!(tags.is_empty());
+    }
+}
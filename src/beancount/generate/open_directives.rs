@@ -1,13 +1,19 @@
 //! Generate open directives for the beancount file.
 
+use std::collections::BTreeSet;
+
 use crate::beancount::account::{Account, AccountType};
 use crate::beancount::directive::Directive;
+use crate::beancount::google::transactions::Transaction;
 
-use crate::beancount::google::GoogleSheet;
+use crate::beancount::source::TransactionSource;
 use crate::beancount::user_settings::UserSettings;
 use crate::error::AppError as Error;
 
-pub(crate) async fn open_directives(user_settings: UserSettings) -> Result<Vec<Directive>, Error> {
+pub(crate) async fn open_directives(
+    user_settings: UserSettings,
+    sources: &[Box<dyn TransactionSource>],
+) -> Result<Vec<Directive>, Error> {
     let mut directives: Vec<Directive> = Vec::new();
 
     // -- Open Equity Accounts -----------------------------------------------------
@@ -33,7 +39,7 @@ pub(crate) async fn open_directives(user_settings: UserSettings) -> Result<Vec<D
     // -- Open Expense Accounts  ---------------------------------------------------------
 
     directives.push(Directive::Comment("Expense accounts".to_string()));
-    directives.extend(open_expenses(user_settings.clone()).await?);
+    directives.extend(open_expenses(user_settings.clone(), sources)?);
     directives.extend(open_config_expenses(user_settings.clone()).await?);
 
     Ok(directives)
@@ -48,11 +54,12 @@ fn open_equity_account(user_settings: UserSettings) -> Result<Vec<Directive>, Er
         institution: String::new(),
         account: "Opening Balances".to_string(),
         sub_account: None,
-        transaction_id: None,
+        name: None,
     };
     directives.push(Directive::Open(
         user_settings.start_date,
         equity_account.clone(),
+        vec![],
         None,
     ));
 
@@ -67,6 +74,7 @@ fn open_config_assets(user_settings: UserSettings) -> Result<Vec<Directive>, Err
             directives.push(Directive::Open(
                 user_settings.start_date,
                 asset_account,
+                vec![],
                 None,
             ));
         }
@@ -83,6 +91,7 @@ fn open_config_income(user_settings: UserSettings) -> Result<Vec<Directive>, Err
             directives.push(Directive::Open(
                 user_settings.start_date,
                 income_account,
+                vec![],
                 None,
             ));
         }
@@ -101,40 +110,62 @@ async fn open_config_liabilities(user_settings: UserSettings) -> Result<Vec<Dire
 
     // open configured liabilities
     for account in user_settings.liabilities.unwrap() {
-        directives.push(Directive::Open(user_settings.start_date, account, None));
+        directives.push(Directive::Open(
+            user_settings.start_date,
+            account,
+            vec![],
+            None,
+        ));
     }
 
     Ok(directives)
 }
 
-// Open expense accounts for each Category in the Google Sheets
-async fn open_expenses(user_settings: UserSettings) -> Result<Vec<Directive>, Error> {
+// Open expense accounts for each Category in the transaction sources
+fn open_expenses(
+    user_settings: UserSettings,
+    sources: &[Box<dyn TransactionSource>],
+) -> Result<Vec<Directive>, Error> {
     let mut directives: Vec<Directive> = Vec::new();
 
-    let googlesheet_accounts = match user_settings.googlesheet_accounts {
-        Some(acc) => acc,
-        None => return Ok(directives),
-    };
-
-    for googlesheet_account in googlesheet_accounts {
-        let google_sheet = GoogleSheet::new(googlesheet_account.clone()).await?;
-        let expense_accounts = google_sheet.expense_accounts().await?;
-        for expense_account in expense_accounts {
-            let beanaccount = Account {
-                account_type: AccountType::Expenses,
-                country: googlesheet_account.country.clone(),
-                institution: googlesheet_account.institution.clone(),
-                account: googlesheet_account.name.clone(),
-                sub_account: Some(expense_account),
-                transaction_id: None,
-            };
-            directives.push(Directive::Open(user_settings.start_date, beanaccount, None));
+    for source in sources {
+        for source_account in source.accounts() {
+            for category in source.categories(&source_account) {
+                let currencies =
+                    foreign_currencies(source.transactions(&source_account), &category);
+                let beanaccount = Account {
+                    account_type: AccountType::Expenses,
+                    country: source_account.country.clone(),
+                    institution: source_account.institution.clone(),
+                    account: source_account.name.clone(),
+                    sub_account: Some(category),
+                    name: None,
+                };
+                directives.push(Directive::Open(
+                    user_settings.start_date,
+                    beanaccount,
+                    currencies,
+                    None,
+                ));
+            }
         }
     }
 
     Ok(directives)
 }
 
+// The local currencies of the transactions in `category` made abroad, which its postings
+// record with a price in the account currency. Split transactions stay in the account currency.
+fn foreign_currencies(transactions: &[Transaction], category: &str) -> Vec<String> {
+    let currencies: BTreeSet<String> = transactions
+        .iter()
+        .filter(|tx| tx.category == category && tx.category_split.is_none() && tx.is_foreign())
+        .map(|tx| tx.local_currency.clone())
+        .collect();
+
+    currencies.into_iter().collect()
+}
+
 async fn open_config_expenses(user_settings: UserSettings) -> Result<Vec<Directive>, Error> {
     let mut directives: Vec<Directive> = Vec::new();
 
@@ -143,6 +174,7 @@ async fn open_config_expenses(user_settings: UserSettings) -> Result<Vec<Directi
             directives.push(Directive::Open(
                 user_settings.start_date,
                 expense_account,
+                vec![],
                 None,
             ));
         }
@@ -0,0 +1,128 @@
+//! Generate open and close directives for pots.
+
+use crate::beancount::directive::Directive;
+use crate::beancount::source::TransactionSource;
+
+use super::pot_account;
+
+/// Open an asset account for each pot on the day it was created, and close it on the
+/// day it was deleted.
+pub(crate) fn pot_directives(sources: &[Box<dyn TransactionSource>]) -> Vec<Directive> {
+    let mut directives: Vec<Directive> = Vec::new();
+
+    for source in sources {
+        for account in source.accounts() {
+            for pot in source.pots(&account) {
+                let beanaccount = pot_account(&account, &pot.name);
+
+                directives.push(Directive::Open(
+                    pot.opened,
+                    beanaccount.clone(),
+                    vec![],
+                    None,
+                ));
+
+                if let Some(closed) = pot.closed {
+                    directives.push(Directive::Close(
+                        closed,
+                        beanaccount,
+                        Some(format!("Pot {} deleted", pot.name)),
+                    ));
+                }
+            }
+        }
+    }
+
+    if !directives.is_empty() {
+        directives.insert(0, Directive::Comment("pot accounts".to_string()));
+    }
+
+    directives
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::beancount::google::transactions::Transaction;
+    use crate::beancount::source::{Pot, SourceAccount};
+
+    use super::*;
+
+    struct PotSource {
+        pots: Vec<Pot>,
+    }
+
+    impl TransactionSource for PotSource {
+        fn label(&self) -> String {
+            "pots".to_string()
+        }
+
+        fn accounts(&self) -> Vec<SourceAccount> {
+            vec![SourceAccount {
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                name: "personal".to_string(),
+            }]
+        }
+
+        fn transactions(&self, _account: &SourceAccount) -> &[Transaction] {
+            &[]
+        }
+
+        fn pots(&self, _account: &SourceAccount) -> Vec<Pot> {
+            self.pots.clone()
+        }
+    }
+
+    #[test]
+    fn should_open_and_close_pots() {
+        // Arrange
+        let sources: Vec<Box<dyn TransactionSource>> = vec![Box::new(PotSource {
+            pots: vec![
+                Pot {
+                    id: "pot_0001".to_string(),
+                    name: "Holiday".to_string(),
+                    currency: "GBP".to_string(),
+                    opened: NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(),
+                    closed: None,
+                },
+                Pot {
+                    id: "pot_0002".to_string(),
+                    name: "Car Tax".to_string(),
+                    currency: "GBP".to_string(),
+                    opened: NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+                    closed: NaiveDate::from_ymd_opt(2024, 5, 20),
+                },
+            ],
+        })];
+
+        // Act
+        let rendered: String = pot_directives(&sources)
+            .iter()
+            .map(|d| d.to_formatted_string())
+            .collect();
+
+        // Assert
+        assert_eq!(
+            rendered,
+            r#"
+* Pot Accounts
+
+2024-01-10 open Assets:GBP:Monzo:Personal:Holiday                  GBP
+2024-02-01 open Assets:GBP:Monzo:Personal:CarTax                   GBP
+; Pot Car Tax deleted.
+2024-05-20 close Assets:GBP:Monzo:Personal:CarTax                  
+"#
+        );
+    }
+
+    #[test]
+    fn should_be_empty_without_pots() {
+        let sources: Vec<Box<dyn TransactionSource>> = vec![Box::new(PotSource { pots: vec![] })];
+
+        assert!(pot_directives(&sources).is_empty());
+    }
+}
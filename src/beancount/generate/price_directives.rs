@@ -0,0 +1,114 @@
+//! Generate price directives for the exchange rates of transactions made abroad.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::beancount::directive::Directive;
+use crate::beancount::money::Decimal;
+use crate::beancount::source::TransactionSource;
+use crate::beancount::transaction::Metadata;
+
+/// The number of decimal places kept in a generated exchange rate.
+const RATE_DECIMAL_PLACES: u32 = 6;
+
+/// A price directive for each day and foreign currency spent, at the rate implied by that
+/// day's transactions, e.g. `2024-06-02 price USD 0.8 GBP`.
+pub(crate) fn price_directives(sources: &[Box<dyn TransactionSource>]) -> Vec<Directive> {
+    // (date, local currency, account currency) -> (local total, account total)
+    let mut totals: BTreeMap<(NaiveDate, String, String), (Decimal, Decimal)> = BTreeMap::new();
+
+    for source in sources {
+        for account in source.accounts() {
+            for tx in source
+                .transactions(&account)
+                .iter()
+                .filter(|tx| tx.is_foreign())
+            {
+                let key = (tx.date, tx.local_currency.clone(), tx.currency.clone());
+                let total = totals.entry(key).or_default();
+                total.0 += tx.local_amount.abs();
+                total.1 += tx.amount.abs();
+            }
+        }
+    }
+
+    let mut directives: Vec<Directive> = totals
+        .into_iter()
+        .map(|((date, local_currency, currency), (local, amount))| {
+            let rate = (amount / local).round_dp(RATE_DECIMAL_PLACES).normalize();
+            Directive::Price(date, local_currency, rate, currency, Metadata::new())
+        })
+        .collect();
+
+    if !directives.is_empty() {
+        directives.insert(0, Directive::Comment("prices".to_string()));
+    }
+
+    directives
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::beancount::google::transactions::Transaction;
+    use crate::beancount::source::SourceAccount;
+
+    use super::*;
+
+    struct FixtureSource {
+        transactions: Vec<Transaction>,
+    }
+
+    impl TransactionSource for FixtureSource {
+        fn label(&self) -> String {
+            "fixture".to_string()
+        }
+
+        fn accounts(&self) -> Vec<SourceAccount> {
+            vec![SourceAccount {
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                name: "Personal".to_string(),
+            }]
+        }
+
+        fn transactions(&self, _account: &SourceAccount) -> &[Transaction] {
+            &self.transactions
+        }
+    }
+
+    #[test]
+    fn should_price_each_day_at_its_implied_rate() {
+        // Arrange
+        let tx = |day: u32, amount: i64, local_amount: i64, local_currency: &str| Transaction {
+            date: NaiveDate::from_ymd_opt(2024, 6, day).unwrap(),
+            amount: Decimal::new(amount, 2),
+            currency: "GBP".to_string(),
+            local_amount: Decimal::new(local_amount, 2),
+            local_currency: local_currency.to_string(),
+            ..Default::default()
+        };
+        let sources: Vec<Box<dyn TransactionSource>> = vec![Box::new(FixtureSource {
+            transactions: vec![
+                tx(2, -80, -100, "USD"),
+                tx(2, -170, -200, "USD"),
+                tx(3, -1000, -1000, "GBP"),
+                tx(3, -300, -900, "PLN"),
+            ],
+        })];
+
+        // Act
+        let rendered: String = price_directives(&sources)
+            .iter()
+            .map(|d| d.to_formatted_string())
+            .collect();
+
+        // Assert
+        assert_eq!(
+            rendered,
+            "\n* Prices\n\n2024-06-02 price USD 0.833333 GBP\n2024-06-03 price PLN 0.333333 GBP\n"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Process transaction source inputs and generate transaction directives.
+
+use crate::beancount::account::Account;
+use crate::beancount::directive::Directive;
+use crate::beancount::generate::classifier::{classify_transaction, review_reason};
+use crate::beancount::google::transactions::Transaction as GoogleTransaction;
+use crate::beancount::source::{InvalidRow, SourceAccount, TransactionSource};
+use crate::beancount::transaction::Transaction as BeancountTransaction;
+use crate::beancount::Beancount;
+use crate::error::AppError as Error;
+
+use super::{
+    prepare_foreign_postings, prepare_from_posting, prepare_split_postings, prepare_to_posting,
+    prepare_transaction,
+};
+
+/// The transaction directives for `sources`, and a row for each transaction that couldn't be
+/// posted, to be reported with the rows that couldn't be parsed.
+pub(crate) fn transaction_directives(
+    beancount: &Beancount,
+    sources: &[Box<dyn TransactionSource>],
+) -> Result<(Vec<Directive>, Vec<InvalidRow>), Error> {
+    let mut directives: Vec<Directive> = Vec::new();
+
+    if sources.is_empty() {
+        return Ok((directives, vec![]));
+    }
+
+    // -- Post Source Transactions--------------------------------------------------------
+
+    let (transactions, unposted) = post_transactions(beancount, sources);
+    directives.push(Directive::Comment("transactions".to_string()));
+    directives.extend(transactions);
+
+    Ok((directives, unposted))
+}
+
+fn post_transactions(
+    beancount: &Beancount,
+    sources: &[Box<dyn TransactionSource>],
+) -> (Vec<Directive>, Vec<InvalidRow>) {
+    let mut directives: Vec<Directive> = Vec::new();
+    let mut unposted: Vec<InvalidRow> = Vec::new();
+    let income_accounts = beancount.user_settings.income.clone().unwrap_or_default();
+    let asset_accounts = beancount.user_settings.assets.clone().unwrap_or_default();
+
+    for source in sources {
+        for account in source.accounts() {
+            for tx in source.transactions(&account) {
+                match post_transaction(beancount, &asset_accounts, &income_accounts, &account, tx) {
+                    Ok(transaction) => {
+                        directives.push(Directive::Transaction(Box::new(transaction)))
+                    }
+                    Err(e) => unposted.push(unposted_row(source.as_ref(), tx, e)),
+                }
+            }
+        }
+    }
+
+    (directives, unposted)
+}
+
+fn post_transaction(
+    beancount: &Beancount,
+    asset_accounts: &[Account],
+    income_accounts: &[Account],
+    account: &SourceAccount,
+    tx: &GoogleTransaction,
+) -> Result<BeancountTransaction, Error> {
+    let from_posting = prepare_from_posting(asset_accounts, income_accounts, account, tx)?;
+    let to_posting = prepare_to_posting(asset_accounts, income_accounts, account, tx)?;
+
+    let (to_posting, from_posting) = prepare_foreign_postings(
+        to_posting,
+        from_posting,
+        tx,
+        beancount.user_settings.price_annotation,
+    );
+
+    let mut postings = prepare_split_postings(to_posting, tx)?;
+    postings.push(from_posting);
+
+    let classification = classify_transaction(asset_accounts, income_accounts, tx)?;
+    let review = review_reason(classification.as_ref(), tx);
+
+    Ok(prepare_transaction(postings, tx, review))
+}
+
+// The row reported for a transaction that couldn't be posted.
+fn unposted_row(source: &dyn TransactionSource, tx: &GoogleTransaction, e: Error) -> InvalidRow {
+    InvalidRow {
+        sheet: tx.sheet.clone().unwrap_or_else(|| source.label()),
+        row: None,
+        column: String::new(),
+        message: format!("transaction {}: {}", tx.id, e),
+        values: vec![
+            tx.id.clone(),
+            tx.date.to_string(),
+            tx.name.clone(),
+            tx.category.clone(),
+            tx.amount.to_string(),
+            tx.currency.clone(),
+        ],
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::NaiveDate;
+    use temp_dir::TempDir;
+
+    use crate::beancount::google::transactions::CategorySplit;
+    use crate::beancount::money::Decimal;
+
+    use super::*;
+
+    struct FixtureSource {
+        transactions: Vec<GoogleTransaction>,
+    }
+
+    impl TransactionSource for FixtureSource {
+        fn label(&self) -> String {
+            "fixture".to_string()
+        }
+
+        fn accounts(&self) -> Vec<SourceAccount> {
+            vec![SourceAccount {
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                name: "Personal".to_string(),
+            }]
+        }
+
+        fn transactions(&self, _account: &SourceAccount) -> &[GoogleTransaction] {
+            &self.transactions
+        }
+    }
+
+    #[test]
+    fn should_report_transactions_that_cant_be_posted() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.child("beancount.yaml"), "start_date: \"2024-01-01\"\n").unwrap();
+        let beancount = Beancount::with_data_dir(dir.path().to_path_buf()).unwrap();
+        let tx = |id: &str, category_split: Option<Vec<CategorySplit>>| GoogleTransaction {
+            id: id.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, 2).unwrap(),
+            name: "Tesco".to_string(),
+            category: "Groceries".to_string(),
+            amount: Decimal::new(-1000, 2),
+            currency: "GBP".to_string(),
+            local_amount: Decimal::new(-1000, 2),
+            local_currency: "GBP".to_string(),
+            category_split,
+            sheet: Some("Personal".to_string()),
+            ..Default::default()
+        };
+        let split = vec![CategorySplit {
+            category: "Groceries".to_string(),
+            amount: Decimal::new(-500, 2),
+        }];
+        let sources: Vec<Box<dyn TransactionSource>> = vec![Box::new(FixtureSource {
+            transactions: vec![tx("tx_0001", None), tx("tx_0002", Some(split))],
+        })];
+
+        // Act
+        let (directives, unposted) = transaction_directives(&beancount, &sources).unwrap();
+
+        // Assert
+        let posted = directives
+            .iter()
+            .filter(|d| matches!(d, Directive::Transaction(_)))
+            .count();
+        assert_eq!(posted, 1);
+        assert_eq!(unposted.len(), 1);
+        assert_eq!(unposted[0].sheet, "Personal");
+        assert_eq!(unposted[0].row, None);
+        assert!(unposted[0].message.starts_with("transaction tx_0002:"));
+    }
+}
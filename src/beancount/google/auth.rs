@@ -0,0 +1,204 @@
+//! Authenticates with the Google Sheets API.
+//!
+//! The interactive installed flow needs a browser to complete its redirect, so unattended
+//! deployments use a service account key or a pre-provisioned refresh token instead. Both are
+//! refreshed automatically when the access token expires.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use google_sheets4::oauth2::{
+    self, authenticator::Authenticator, authorized_user::AuthorizedUserSecret,
+    hyper::client::HttpConnector, hyper_rustls::HttpsConnector, ApplicationSecret,
+    ServiceAccountKey,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::error::AppError as Error;
+
+/// The file in the secrets directory that access tokens are cached in.
+const TOKEN_CACHE_FILE_NAME: &str = "tokencache.json";
+
+/// How to authenticate with Google, set as `google_auth` in the application configuration.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum GoogleAuth {
+    /// Sign in through the browser with an OAuth client, caching the tokens.
+    #[default]
+    Installed,
+    /// Authenticate as a service account the sheets have been shared with.
+    ServiceAccount {
+        #[serde(default = "default_service_account_file")]
+        key_file: PathBuf,
+    },
+    /// Use a refresh token obtained beforehand, e.g. with
+    /// `gcloud auth application-default login`.
+    AuthorizedUser {
+        #[serde(default = "default_authorized_user_file")]
+        key_file: PathBuf,
+    },
+    /// Send no credentials, for a local stand-in for the API.
+    Anonymous,
+}
+
+fn default_service_account_file() -> PathBuf {
+    PathBuf::from("service_account.json")
+}
+
+fn default_authorized_user_file() -> PathBuf {
+    PathBuf::from("authorized_user.json")
+}
+
+/// A Google API authenticator.
+pub type GoogleAuthenticator = Authenticator<HttpsConnector<HttpConnector>>;
+
+impl GoogleAuth {
+    /// Build an authenticator from the credentials in `secrets_dir`, or `None` if no credentials
+    /// are sent.
+    ///
+    /// Key files are resolved against `secrets_dir` unless they are absolute.
+    pub async fn authenticator(
+        &self,
+        secrets_dir: &Path,
+    ) -> Result<Option<GoogleAuthenticator>, Error> {
+        let token_file = secrets_dir.join(TOKEN_CACHE_FILE_NAME);
+
+        let auth = match self {
+            GoogleAuth::Installed => {
+                let credentials_file = secrets_dir.join("credentials.json");
+                let secret: ApplicationSecret = read_credentials(&credentials_file)?;
+
+                oauth2::InstalledFlowAuthenticator::builder(
+                    secret,
+                    oauth2::InstalledFlowReturnMethod::HTTPRedirect,
+                )
+                .persist_tokens_to_disk(token_file)
+                .build()
+                .await
+                .map_err(|e| invalid_credentials(&credentials_file, e))?
+            }
+            GoogleAuth::ServiceAccount { key_file } => {
+                let key_file = secrets_dir.join(key_file);
+                let key: ServiceAccountKey = read_credentials(&key_file)?;
+
+                oauth2::ServiceAccountAuthenticator::builder(key)
+                    .persist_tokens_to_disk(token_file)
+                    .build()
+                    .await
+                    .map_err(|e| invalid_credentials(&key_file, e))?
+            }
+            GoogleAuth::AuthorizedUser { key_file } => {
+                let key_file = secrets_dir.join(key_file);
+                let secret: AuthorizedUserSecret = read_credentials(&key_file)?;
+
+                oauth2::AuthorizedUserAuthenticator::builder(secret)
+                    .persist_tokens_to_disk(token_file)
+                    .build()
+                    .await
+                    .map_err(|e| invalid_credentials(&key_file, e))?
+            }
+            GoogleAuth::Anonymous => return Ok(None),
+        };
+
+        Ok(Some(auth))
+    }
+}
+
+fn read_credentials<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    if !path.exists() {
+        return Err(Error::GoogleCredentialsMissing(
+            path.to_string_lossy().to_string(),
+        ));
+    }
+
+    let contents = fs::read_to_string(path)?;
+
+    serde_json::from_str(&contents).map_err(|e| invalid_credentials(path, e))
+}
+
+fn invalid_credentials(path: &Path, e: impl std::fmt::Display) -> Error {
+    Error::GoogleCredentialsInvalid(path.to_string_lossy().to_string(), e.to_string())
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn should_deserialise_auth_modes() {
+        let auth: GoogleAuth = serde_yaml::from_str("mode: service_account").unwrap();
+        assert_eq!(
+            auth,
+            GoogleAuth::ServiceAccount {
+                key_file: PathBuf::from("service_account.json")
+            }
+        );
+
+        let auth: GoogleAuth =
+            serde_yaml::from_str("mode: authorized_user\nkey_file: /run/secrets/google.json")
+                .unwrap();
+        assert_eq!(
+            auth,
+            GoogleAuth::AuthorizedUser {
+                key_file: PathBuf::from("/run/secrets/google.json")
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_missing_credentials() {
+        let dir = TempDir::new().unwrap();
+        let auth = GoogleAuth::ServiceAccount {
+            key_file: default_service_account_file(),
+        };
+
+        let result = auth.authenticator(dir.path()).await;
+
+        assert!(matches!(result, Err(Error::GoogleCredentialsMissing(_))));
+    }
+
+    #[tokio::test]
+    async fn should_report_invalid_credentials() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.child("service_account.json"),
+            r#"{"type": "service_account"}"#,
+        )
+        .unwrap();
+        let auth = GoogleAuth::ServiceAccount {
+            key_file: default_service_account_file(),
+        };
+
+        let result = auth.authenticator(dir.path()).await;
+
+        assert!(matches!(result, Err(Error::GoogleCredentialsInvalid(_, _))));
+    }
+
+    #[tokio::test]
+    async fn should_build_authorized_user_authenticator() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.child("authorized_user.json"),
+            r#"{
+  "client_id": "client.apps.googleusercontent.com",
+  "client_secret": "secret",
+  "refresh_token": "refresh_0001",
+  "type": "authorized_user"
+}"#,
+        )
+        .unwrap();
+        let auth = GoogleAuth::AuthorizedUser {
+            key_file: default_authorized_user_file(),
+        };
+
+        let result = auth.authenticator(dir.path()).await;
+
+        assert!(matches!(result, Ok(Some(_))));
+    }
+}
@@ -0,0 +1,201 @@
+//! Maps transaction fields to the columns of a Monzo sheet or export by header name.
+//!
+//! The header row is matched against a `ColumnMapping`, so columns can appear in any order and
+//! extra user-added columns are ignored. Header names are compared ignoring case and surrounding
+//! whitespace.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError as Error;
+
+/// The header names of the columns holding each transaction field.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ColumnMapping {
+    pub id: String,
+    pub date: String,
+    pub payment_type: String,
+    pub name: String,
+    pub category: String,
+    pub amount: String,
+    pub currency: String,
+    pub local_amount: String,
+    pub local_currency: String,
+    pub notes: String,
+    pub description: String,
+    pub category_split: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping {
+            id: "Transaction ID".to_string(),
+            date: "Date".to_string(),
+            payment_type: "Type".to_string(),
+            name: "Name".to_string(),
+            category: "Category".to_string(),
+            amount: "Amount".to_string(),
+            currency: "Currency".to_string(),
+            local_amount: "Local amount".to_string(),
+            local_currency: "Local currency".to_string(),
+            notes: "Notes and #tags".to_string(),
+            description: "Description".to_string(),
+            category_split: "Category split".to_string(),
+        }
+    }
+}
+
+/// The positions of each transaction field in a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnIndex {
+    pub id: usize,
+    pub date: usize,
+    pub payment_type: usize,
+    pub name: usize,
+    pub category: usize,
+    pub amount: usize,
+    pub currency: usize,
+    pub local_amount: usize,
+    pub local_currency: usize,
+    pub notes: Option<usize>,
+    pub description: Option<usize>,
+    pub category_split: Option<usize>,
+    /// The header row the positions were found in.
+    pub headers: Vec<String>,
+}
+
+impl ColumnIndex {
+    /// The header of the column at `position`.
+    pub fn header(&self, position: usize) -> &str {
+        self.headers
+            .get(position)
+            .map(|h| h.as_str())
+            .unwrap_or_default()
+    }
+}
+
+impl ColumnMapping {
+    /// Find the position of each field in `headers`.
+    ///
+    /// Returns an error naming every required header that is missing from `sheet`.
+    pub fn index(&self, sheet: &str, headers: &[String]) -> Result<ColumnIndex, Error> {
+        let find = |header: &str| {
+            headers
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(header.trim()))
+        };
+
+        let mut missing: Vec<String> = Vec::new();
+        let mut require = |header: &str| {
+            find(header).unwrap_or_else(|| {
+                missing.push(format!("\"{}\"", header));
+                0
+            })
+        };
+
+        let index = ColumnIndex {
+            id: require(&self.id),
+            date: require(&self.date),
+            payment_type: require(&self.payment_type),
+            name: require(&self.name),
+            category: require(&self.category),
+            amount: require(&self.amount),
+            currency: require(&self.currency),
+            local_amount: require(&self.local_amount),
+            local_currency: require(&self.local_currency),
+            notes: find(&self.notes),
+            description: find(&self.description),
+            category_split: find(&self.category_split),
+            headers: headers.to_vec(),
+        };
+
+        if !missing.is_empty() {
+            return Err(Error::MissingColumnsError(
+                sheet.to_string(),
+                missing.join(", "),
+            ));
+        }
+
+        Ok(index)
+    }
+}
+
+// -- Tests --------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn should_index_reordered_columns() {
+        let headers = headers(&[
+            "My Notes",
+            "Date",
+            "Transaction ID",
+            "Type",
+            "Name",
+            "Category",
+            "Amount",
+            "Currency",
+            "Local Amount",
+            "Local currency",
+            "Description",
+        ]);
+
+        let index = ColumnMapping::default()
+            .index("Personal", &headers)
+            .unwrap();
+
+        assert_eq!(index.id, 2);
+        assert_eq!(index.date, 1);
+        assert_eq!(index.local_amount, 8);
+        assert_eq!(index.notes, None);
+        assert_eq!(index.description, Some(10));
+        assert_eq!(index.category_split, None);
+    }
+
+    #[test]
+    fn should_use_configured_headers() {
+        let mapping = ColumnMapping {
+            category: "Budget".to_string(),
+            ..Default::default()
+        };
+        let headers = headers(&[
+            "Transaction ID",
+            "Date",
+            "Type",
+            "Name",
+            "Budget",
+            "Amount",
+            "Currency",
+            "Local amount",
+            "Local currency",
+        ]);
+
+        let index = mapping.index("Personal", &headers).unwrap();
+
+        assert_eq!(index.category, 4);
+    }
+
+    #[test]
+    fn should_list_missing_headers() {
+        let headers = headers(&["Transaction ID", "Date", "Type", "Name", "Category"]);
+
+        let result = ColumnMapping::default().index("Personal", &headers);
+
+        match result {
+            Err(Error::MissingColumnsError(sheet, missing)) => {
+                assert_eq!(sheet, "Personal");
+                assert_eq!(
+                    missing,
+                    r#""Amount", "Currency", "Local amount", "Local currency""#
+                );
+            }
+            _ => panic!("Expected MissingColumnsError, got {:?}", result),
+        }
+    }
+}
@@ -1,27 +1,87 @@
 //! Authenticates and accesses a Google Sheet.
 //!
 
-pub mod expense_accounts;
+pub mod auth;
+pub mod columns;
 pub mod sheets;
 pub mod transactions;
 
-use std::{fs::File, io::BufReader};
+use std::path::PathBuf;
 
+use futures::{stream, StreamExt, TryStreamExt};
+
+use auth::GoogleAuth;
+use columns::ColumnMapping;
 use google_sheets4::{
+    client::NoToken,
     hyper::{self, client::HttpConnector},
-    hyper_rustls, oauth2, Sheets,
+    hyper_rustls, Sheets,
 };
 use hyper_rustls::HttpsConnector;
 use serde::{Deserialize, Serialize};
 use transactions::Transaction;
 
+use crate::configuration::ApplicationSettings;
 use crate::error::AppError as Error;
 
+use super::source::{InvalidRow, SourceAccount, TransactionSource};
+
+/// An authenticated Google Sheets API hub.
+pub type SheetsHub = Sheets<HttpsConnector<HttpConnector>>;
+
+/// Where and how to reach the Google Sheets API.
+#[derive(Debug, Clone, Default)]
+pub struct GoogleApi {
+    /// The root URL of the API, if not Google's, e.g. a local stand-in.
+    pub base_url: Option<String>,
+    pub auth: GoogleAuth,
+    /// The directory holding the credentials and token cache.
+    pub secrets_dir: PathBuf,
+}
+
+impl From<&ApplicationSettings> for GoogleApi {
+    fn from(settings: &ApplicationSettings) -> Self {
+        GoogleApi {
+            base_url: settings.google_api_url.clone(),
+            auth: settings.google_auth.clone(),
+            secrets_dir: settings.secrets_dir.clone(),
+        }
+    }
+}
+
+impl GoogleApi {
+    /// Create an authenticated Google Sheets API hub.
+    pub async fn hub(&self) -> Result<SheetsHub, Error> {
+        let client = hyper::Client::builder().build(
+            hyper_rustls::HttpsConnectorBuilder::new()
+                .with_native_roots()
+                .https_or_http()
+                .enable_http1()
+                .build(),
+        );
+
+        let mut hub = match self.auth.authenticator(&self.secrets_dir).await? {
+            Some(auth) => Sheets::new(client, auth),
+            None => Sheets::new(client, NoToken),
+        };
+
+        if let Some(base_url) = &self.base_url {
+            // the hub appends paths such as "v4/spreadsheets" directly
+            let base_url = format!("{}/", base_url.trim_end_matches('/'));
+            hub.base_url(base_url.clone());
+            hub.root_url(base_url);
+        }
+
+        Ok(hub)
+    }
+}
+
 /// A struct for representing a Google Sheet.
 pub struct GoogleSheet {
-    pub hub: Sheets<HttpsConnector<HttpConnector>>,
+    pub hub: SheetsHub,
     pub account: GoogleSheetAccount,
     pub transactions: Option<Vec<Transaction>>,
+    pub invalid_rows: Vec<InvalidRow>,
 }
 
 /// A struct to represent a Google Sheet account
@@ -32,44 +92,182 @@ pub struct GoogleSheetAccount {
     pub name: String,
     pub sheet_name: String,
     pub sheet_id: String,
+    /// Header names of the transaction columns, where they differ from the Monzo defaults.
+    #[serde(default)]
+    pub columns: ColumnMapping,
 }
+
+impl From<&GoogleSheetAccount> for SourceAccount {
+    fn from(account: &GoogleSheetAccount) -> Self {
+        SourceAccount {
+            country: account.country.clone(),
+            institution: account.institution.clone(),
+            name: account.name.clone(),
+        }
+    }
+}
+
 impl GoogleSheet {
     /// Create an authenticated GoogleSheet instance.
-    pub async fn new(account: GoogleSheetAccount) -> Result<Self, Error> {
-        let config =
-            crate::configuration::get_configuration().expect("Failed to read configuration.");
-
-        let credentials_file =
-            File::open(config.application.secrets_dir.join("credentials.json")).unwrap();
-        let token_file = config.application.secrets_dir.join("tokencache.json");
-        let reader = BufReader::new(credentials_file);
-        let secret: oauth2::ApplicationSecret = serde_json::from_reader(reader).unwrap();
-
-        let auth = oauth2::InstalledFlowAuthenticator::builder(
-            secret,
-            oauth2::InstalledFlowReturnMethod::HTTPRedirect,
-        )
-        .persist_tokens_to_disk(token_file)
-        .build()
-        .await?;
-
-        let hub = Sheets::new(
-            hyper::Client::builder().build(
-                hyper_rustls::HttpsConnectorBuilder::new()
-                    .with_native_roots()
-                    .https_or_http()
-                    .enable_http1()
-                    .build(),
-            ),
-            auth,
-        );
+    pub async fn new(api: &GoogleApi, account: GoogleSheetAccount) -> Result<Self, Error> {
+        let hub = api.hub().await?;
+
+        GoogleSheet::with_hub(hub, account).await
+    }
+
+    /// Fetch each of `accounts` once, sharing one authenticated hub and keeping at most
+    /// `concurrency` sheets downloading at a time. Sheets are returned in the order given.
+    pub async fn fetch_all(
+        api: &GoogleApi,
+        accounts: &[GoogleSheetAccount],
+        concurrency: usize,
+    ) -> Result<Vec<Self>, Error> {
+        if accounts.is_empty() {
+            return Ok(vec![]);
+        }
 
-        let transactions = GoogleSheet::load_transactions(&hub, &account).await?;
+        let hub = api.hub().await?;
+
+        stream::iter(accounts.iter().cloned())
+            .map(|account| GoogleSheet::with_hub(hub.clone(), account))
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await
+    }
+
+    /// Create a GoogleSheet instance by loading the transactions for `account` with `hub`.
+    pub async fn with_hub(hub: SheetsHub, account: GoogleSheetAccount) -> Result<Self, Error> {
+        let (transactions, invalid_rows) = GoogleSheet::load_transactions(&hub, &account).await?;
 
         Ok(GoogleSheet {
             hub,
             account,
             transactions,
+            invalid_rows,
         })
     }
 }
+
+impl TransactionSource for GoogleSheet {
+    fn label(&self) -> String {
+        self.account.sheet_name.clone()
+    }
+
+    fn accounts(&self) -> Vec<SourceAccount> {
+        vec![SourceAccount::from(&self.account)]
+    }
+
+    fn transactions(&self, account: &SourceAccount) -> &[Transaction] {
+        if account != &SourceAccount::from(&self.account) {
+            return &[];
+        }
+
+        self.transactions.as_deref().unwrap_or_default()
+    }
+
+    fn invalid_rows(&self) -> &[InvalidRow] {
+        &self.invalid_rows
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use wiremock::{
+        matchers::{method, path, path_regex},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::beancount::money::Decimal;
+
+    use super::*;
+
+    pub(crate) const SHEET_ID: &str = "sheet_0001";
+    pub(crate) const SPREADSHEET: &str =
+        include_str!("../../../tests/fixtures/google/spreadsheet.json");
+    pub(crate) const VALUES: &str = include_str!("../../../tests/fixtures/google/values.json");
+
+    /// Start a stand-in Sheets API serving the recorded fixtures for `SHEET_ID`.
+    pub(crate) async fn mock_server() -> MockServer {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path_regex(format!(
+                "^/v4/spreadsheets/{}/values/.+$",
+                SHEET_ID
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(VALUES, "application/json"))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path(format!("/v4/spreadsheets/{}", SHEET_ID)))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(SPREADSHEET, "application/json"))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    /// Settings for reaching `server` without credentials.
+    pub(crate) fn mock_api(server: &MockServer) -> GoogleApi {
+        GoogleApi {
+            base_url: Some(server.uri()),
+            auth: GoogleAuth::Anonymous,
+            secrets_dir: PathBuf::new(),
+        }
+    }
+
+    pub(crate) fn test_account() -> GoogleSheetAccount {
+        GoogleSheetAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+            sheet_name: "Personal Account Transactions".to_string(),
+            sheet_id: SHEET_ID.to_string(),
+            columns: ColumnMapping::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_fetch_transactions_from_api() {
+        // Arrange
+        let server = mock_server().await;
+
+        // Act
+        let sheets = GoogleSheet::fetch_all(&mock_api(&server), &[test_account()], 2)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(sheets.len(), 1);
+        let account = SourceAccount::from(&test_account());
+        let transactions = sheets[0].transactions(&account);
+        assert_eq!(transactions.len(), 3);
+        assert_eq!(transactions[0].id, "tx_0001");
+        assert_eq!(transactions[2].amount, Decimal::new(250000, 2));
+        assert!(sheets[0].invalid_rows().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_list_sheets_from_api() {
+        // Arrange
+        let server = mock_server().await;
+        let sheet = GoogleSheet::new(&mock_api(&server), test_account())
+            .await
+            .unwrap();
+
+        // Act
+        let sheets = sheet.sheets().await.unwrap();
+
+        // Assert
+        assert_eq!(
+            sheets,
+            Some(vec![
+                "Personal Account Transactions".to_string(),
+                "Budget".to_string()
+            ])
+        );
+    }
+}
@@ -1,47 +1,79 @@
 //! Get transactions from a Google Sheet.
 
 use chrono::NaiveDate;
-use google_sheets4::{hyper::client::HttpConnector, hyper_rustls, Sheets};
-use hyper_rustls::HttpsConnector;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::value::Value;
 
+use crate::beancount::money::{exponent, Decimal};
+use crate::beancount::source::{InvalidRow, POT_TRANSFER};
 use crate::error::AppError as Error;
 
-use super::{GoogleSheet, GoogleSheetAccount};
+use super::columns::ColumnIndex;
+use super::{GoogleSheet, GoogleSheetAccount, SheetsHub};
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Clone, Deserialize)]
 pub struct Transaction {
     pub id: String,
     pub date: NaiveDate,
     pub payment_type: String,
     pub name: String,
     pub category: String,
-    pub amount: i64,
+    pub amount: Decimal,
     pub currency: String,
-    pub local_amount: i64,
+    pub local_amount: Decimal,
     pub local_currency: String,
     pub notes: Option<String>,
     pub description: Option<String>,
+    /// The amount spent in each category when the transaction is split.
     pub category_split: Option<Vec<CategorySplit>>,
+    /// The name of the pot the money was moved to, moved from, or paid from.
+    pub pot: Option<String>,
+    /// The sheet or export the transaction was read from.
+    pub sheet: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategorySplit {
     pub category: String,
-    pub amount: f64,
+    pub amount: Decimal,
 }
 
-impl GoogleSheet {
-    pub async fn transactions(&self) -> Option<&Vec<Transaction>> {
-        self.transactions.as_ref()
+impl Transaction {
+    /// Check that the category split, if any, adds up to the transaction amount.
+    pub fn check_category_split(&self) -> Result<(), Error> {
+        let Some(splits) = &self.category_split else {
+            return Ok(());
+        };
+
+        let total: Decimal = splits.iter().map(|split| split.amount).sum();
+
+        if total != self.amount {
+            let precision = exponent(&self.currency) as usize;
+            return Err(Error::CategorySplitTotalError(
+                self.id.clone(),
+                format!("{:.*}", precision, total),
+                format!("{:.*}", precision, self.amount),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the transaction was made in a currency other than the account's.
+    pub fn is_foreign(&self) -> bool {
+        !self.local_currency.is_empty()
+            && self.local_currency != self.currency
+            && !self.local_amount.is_zero()
     }
+}
 
+impl GoogleSheet {
     pub(crate) async fn load_transactions(
-        hub: &Sheets<HttpsConnector<HttpConnector>>,
+        hub: &SheetsHub,
         account: &GoogleSheetAccount,
-    ) -> Result<Option<Vec<Transaction>>, Error> {
-        let range = format!("{}!A:P", &account.sheet_name);
+    ) -> Result<(Option<Vec<Transaction>>, Vec<InvalidRow>), Error> {
+        // the whole sheet, so that columns are found by header wherever they are
+        let range = format!("'{}'", account.sheet_name.replace('\'', "''"));
 
         let result = hub
             .spreadsheets()
@@ -53,58 +85,157 @@ impl GoogleSheet {
 
         let values = match result.1.values {
             Some(values) => values,
-            None => return Ok(None),
+            None => return Ok((None, vec![])),
         };
 
-        let mut transactions: Vec<Transaction> = Vec::new();
-
-        for row in values.iter().skip(1) {
-            let transaction = Transaction {
-                id: parse_string(row.first()).unwrap_or_default(),
-                date: parse_date(row[1].clone()),
-                payment_type: parse_string(row.get(3)).unwrap_or_default(),
-                name: parse_string(row.get(4)).unwrap_or_default(),
-                category: parse_string(row.get(6)).unwrap_or_default(),
-                amount: parse_float(row[7].clone()),
-                currency: parse_string(row.get(8)).unwrap_or_default(),
-                local_amount: parse_float(row[9].clone()),
-                local_currency: parse_string(row.get(10)).unwrap_or_default(),
-                notes: parse_string(row.get(11)),
-                description: parse_string(row.get(14)),
-                category_split: parse_category_split(row.get(15))?,
-            };
-            transactions.push(transaction);
-        }
+        let headers: Vec<String> = values
+            .first()
+            .map(|row| {
+                row.iter()
+                    .map(|v| v.as_str().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let index = account.columns.index(&account.sheet_name, &headers)?;
+
+        let (transactions, invalid_rows) = parse_rows(&values[1..], &index, &account.sheet_name);
+
+        Ok((Some(transactions), invalid_rows))
+    }
+}
 
-        Ok(Some(transactions))
+/// Parse the rows following a header row, setting aside any that can't be parsed.
+pub(crate) fn parse_rows(
+    rows: &[Vec<Value>],
+    index: &ColumnIndex,
+    sheet: &str,
+) -> (Vec<Transaction>, Vec<InvalidRow>) {
+    let mut transactions: Vec<Transaction> = Vec::new();
+    let mut invalid_rows: Vec<InvalidRow> = Vec::new();
+
+    for (i, row) in rows.iter().enumerate() {
+        match parse_row(row, index) {
+            Ok(transaction) => transactions.push(Transaction {
+                sheet: Some(sheet.to_string()),
+                ..transaction
+            }),
+            Err((column, message)) => invalid_rows.push(InvalidRow {
+                sheet: sheet.to_string(),
+                // rows are numbered from 1 and the header is the first
+                row: Some(i + 2),
+                column: index.header(column).to_string(),
+                message,
+                values: row.iter().map(cell_text).collect(),
+            }),
+        }
     }
+
+    (transactions, invalid_rows)
+}
+
+// Parse a row of Monzo transaction columns, returning the position of the failing column and
+// the reason on error.
+fn parse_row(row: &[Value], index: &ColumnIndex) -> Result<Transaction, (usize, String)> {
+    let column = |i: Option<usize>| i.and_then(|i| row.get(i));
+    let at = |i: usize| move |message: String| (i, message);
+
+    let payment_type = parse_string(row.get(index.payment_type)).unwrap_or_default();
+    let name = parse_string(row.get(index.name)).unwrap_or_default();
+    let pot = (payment_type == POT_TRANSFER).then(|| name.clone());
+
+    let currency = parse_string(row.get(index.currency)).unwrap_or_default();
+    let local_currency = parse_string(row.get(index.local_currency)).unwrap_or_default();
+
+    let transaction = Transaction {
+        id: parse_string(row.get(index.id))
+            .ok_or_else(|| (index.id, "missing transaction id".to_string()))?,
+        date: parse_date(row.get(index.date)).map_err(at(index.date))?,
+        payment_type,
+        name,
+        category: parse_string(row.get(index.category)).unwrap_or_default(),
+        amount: parse_amount(row.get(index.amount), &currency).map_err(at(index.amount))?,
+        local_amount: parse_amount(row.get(index.local_amount), &local_currency)
+            .map_err(at(index.local_amount))?,
+        currency,
+        local_currency,
+        notes: parse_string(column(index.notes)),
+        description: parse_string(column(index.description)),
+        category_split: parse_category_split(column(index.category_split))
+            .map_err(|e| (index.category_split.unwrap_or_default(), e.to_string()))?,
+        pot,
+        sheet: None,
+    };
+
+    transaction
+        .check_category_split()
+        .map_err(|e| (index.category_split.unwrap_or_default(), e.to_string()))?;
+
+    Ok(transaction)
 }
 
 fn parse_string(value: Option<&Value>) -> Option<String> {
     value
         .and_then(|v| v.as_str()) // Try to get the &str from Value
         .filter(|s| !s.is_empty())
-        .map(|s| s.replace('/', "_"))
-        .map(|s| s.replace('&', "")) // Check if the string is not empty
         .map(|s| s.to_string()) // Convert the &str to String
 }
 
-// returns a minor unit currency i.e. "-500.00" -> -50000
-fn parse_float(amount: Value) -> i64 {
-    let cleaned_input = amount.to_string().replace(['"', '.'], "");
-    let value: i64 = cleaned_input.parse().expect("Invalid number format");
+// the text of a cell, whether the API returned it as a string or a number
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
 
-    value
+// returns an exact amount with the decimal places of `currency` i.e. "-500.00" -> -500.00,
+// "5.5" -> 5.50, "1,234.56" -> 1234.56, "1,500" JPY -> 1500, "1.234" KWD -> 1.234
+fn parse_amount(amount: Option<&Value>, currency: &str) -> Result<Decimal, String> {
+    let text = amount.map(cell_text).unwrap_or_default();
+    let cleaned = text.trim().replace(',', "");
+    let invalid = || format!("invalid amount \"{}\"", text);
+
+    if cleaned.is_empty() {
+        return Err("missing amount".to_string());
+    }
+
+    let (negative, digits) = match cleaned.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, cleaned.strip_prefix('+').unwrap_or(&cleaned)),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || !is_digits(whole)
+        || !is_digits(fraction)
+        || fraction.len() > exponent(currency) as usize
+    {
+        return Err(invalid());
+    }
+
+    let mut value =
+        Decimal::from_str_exact(&format!("0{}.{}0", whole, fraction)).map_err(|_| invalid())?;
+    value.rescale(exponent(currency));
+
+    Ok(if negative { -value } else { value })
 }
 
-fn parse_date(date: Value) -> NaiveDate {
-    let date_str = date.to_string().replace('"', "");
-    NaiveDate::parse_from_str(&date_str, "%d/%m/%Y").unwrap()
+fn parse_date(date: Option<&Value>) -> Result<NaiveDate, String> {
+    let date_str = date.map(cell_text).unwrap_or_default();
+
+    if date_str.trim().is_empty() {
+        return Err("missing date".to_string());
+    }
+
+    NaiveDate::parse_from_str(date_str.trim(), "%d/%m/%Y")
+        .map_err(|_| format!("invalid date \"{}\", expected DD/MM/YYYY", date_str))
 }
 
 fn parse_category_split(input: Option<&Value>) -> Result<Option<Vec<CategorySplit>>, Error> {
     match input {
-        Some(Value::String(s)) => {
+        Some(Value::String(s)) if !s.trim().is_empty() => {
             // Split the string by commas
             let splits: Vec<&str> = s.split(',').collect();
             let mut category_splits = Vec::new();
@@ -116,7 +247,7 @@ fn parse_category_split(input: Option<&Value>) -> Result<Option<Vec<CategorySpli
                 if parts.len() == 2 {
                     // Extract category and value
                     let category = parts[0].to_string().trim().to_string();
-                    match parts[1].parse::<f64>() {
+                    match parts[1].trim().parse::<Decimal>() {
                         Ok(amount) => category_splits.push(CategorySplit { category, amount }),
                         Err(_) => {
                             return Err(Error::CategoryParseError(format!(
@@ -133,7 +264,7 @@ fn parse_category_split(input: Option<&Value>) -> Result<Option<Vec<CategorySpli
             // Return the vector of CategorySplit
             Ok(Some(category_splits))
         }
-        Some(_) => Ok(None), // Return None if the input value is not a string
+        Some(_) => Ok(None), // Return None if the input value is not a non-empty string
         None => Ok(None),    // Return None if the input is None
     }
 }
@@ -142,6 +273,7 @@ fn parse_category_split(input: Option<&Value>) -> Result<Option<Vec<CategorySpli
 
 #[cfg(test)]
 mod tests {
+    use super::super::columns::ColumnMapping;
     use super::*;
 
     #[test]
@@ -149,6 +281,12 @@ mod tests {
         let value = Some(Value::String("test".to_string()));
         assert_eq!(parse_string(value.as_ref()), Some("test".to_string()));
 
+        let value = Some(Value::String("Food & Drink/Bars".to_string()));
+        assert_eq!(
+            parse_string(value.as_ref()),
+            Some("Food & Drink/Bars".to_string())
+        );
+
         let value = Some(Value::String("".to_string()));
         assert_eq!(parse_string(value.as_ref()), None);
 
@@ -157,18 +295,160 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_float() {
-        let value = Value::String("1.23".to_string());
-        assert_eq!(parse_float(value), 123);
+    fn test_parse_amount() {
+        let amount = |s: &str| parse_amount(Some(&Value::String(s.to_string())), "GBP");
+
+        assert_eq!(amount("1.23"), Ok(Decimal::new(123, 2)));
+        assert_eq!(amount("5"), Ok(Decimal::new(5, 0)));
+        assert_eq!(amount("5.5"), Ok(Decimal::new(55, 1)));
+        assert_eq!(amount("0.29"), Ok(Decimal::new(29, 2)));
+        assert_eq!(amount("-0.05"), Ok(Decimal::new(-5, 2)));
+        assert_eq!(amount("1,234.56"), Ok(Decimal::new(123456, 2)));
+        assert_eq!(
+            parse_amount(Some(&Value::Number(12.into())), "GBP"),
+            Ok(Decimal::new(12, 0))
+        );
+        assert!(amount("").is_err());
+        assert!(amount("1.234").is_err());
+        assert!(amount("twelve").is_err());
+        assert!(parse_amount(None, "GBP").is_err());
+
+        let yen = Value::String("1,500".to_string());
+        assert_eq!(parse_amount(Some(&yen), "JPY"), Ok(Decimal::new(1500, 0)));
+        let yen = Value::String("1,500.5".to_string());
+        assert!(parse_amount(Some(&yen), "JPY").is_err());
+        let dinar = Value::String("-1.234".to_string());
+        assert_eq!(
+            parse_amount(Some(&dinar), "KWD"),
+            Ok(Decimal::new(-1234, 3))
+        );
     }
 
     #[test]
     fn test_parse_date() {
         let value = Value::String("01/02/2021".to_string());
         assert_eq!(
-            parse_date(value),
-            NaiveDate::from_ymd_opt(2021, 2, 1).unwrap()
+            parse_date(Some(&value)),
+            Ok(NaiveDate::from_ymd_opt(2021, 2, 1).unwrap())
         );
+
+        let value = Value::String("2021-02-01".to_string());
+        assert!(parse_date(Some(&value)).is_err());
+        assert!(parse_date(None).is_err());
+    }
+
+    #[test]
+    fn test_parse_row_by_header() {
+        let headers: Vec<String> = [
+            "Date",
+            "My label",
+            "Name",
+            "Transaction ID",
+            "Type",
+            "Category",
+            "Currency",
+            "Amount",
+            "Local currency",
+            "Local amount",
+            "Description",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+        let index = ColumnMapping::default()
+            .index("Personal", &headers)
+            .unwrap();
+        let row: Vec<Value> = [
+            "02/06/2024",
+            "work",
+            "Tesco",
+            "tx_0001",
+            "Card payment",
+            "Groceries",
+            "GBP",
+            "-12.34",
+            "GBP",
+            "-12.34",
+        ]
+        .iter()
+        .map(|v| Value::String(v.to_string()))
+        .collect();
+
+        let (transactions, invalid_rows) = parse_rows(&[row], &index, "Personal");
+
+        assert!(invalid_rows.is_empty());
+        let tx = &transactions[0];
+
+        assert_eq!(tx.id, "tx_0001");
+        assert_eq!(tx.date, NaiveDate::from_ymd_opt(2024, 6, 2).unwrap());
+        assert_eq!(tx.name, "Tesco");
+        assert_eq!(tx.amount, Decimal::new(-1234, 2));
+        assert_eq!(tx.notes, None);
+        assert_eq!(tx.description, None);
+    }
+
+    #[test]
+    fn test_parse_rows_sets_aside_invalid_rows() {
+        let headers: Vec<String> = [
+            "Transaction ID",
+            "Date",
+            "Type",
+            "Name",
+            "Category",
+            "Amount",
+            "Currency",
+            "Local amount",
+            "Local currency",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+        let index = ColumnMapping::default()
+            .index("Personal", &headers)
+            .unwrap();
+        let row = |values: &[&str]| -> Vec<Value> {
+            values
+                .iter()
+                .map(|v| Value::String(v.to_string()))
+                .collect()
+        };
+        let rows = vec![
+            row(&[
+                "tx_0001",
+                "02/06/2024",
+                "Card payment",
+                "Tesco",
+                "Groceries",
+                "-12.34",
+                "GBP",
+                "-12.34",
+                "GBP",
+            ]),
+            row(&[
+                "tx_0002",
+                "03/06/2024",
+                "Card payment",
+                "Cafe",
+                "Eating out",
+                "-3.x",
+                "GBP",
+                "-3.50",
+                "GBP",
+            ]),
+            row(&["tx_0003", "04/06/2024"]),
+        ];
+
+        let (transactions, invalid_rows) = parse_rows(&rows, &index, "Personal");
+
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(invalid_rows.len(), 2);
+        assert_eq!(invalid_rows[0].sheet, "Personal");
+        assert_eq!(invalid_rows[0].row, Some(3));
+        assert_eq!(invalid_rows[0].column, "Amount");
+        assert_eq!(invalid_rows[0].message, "invalid amount \"-3.x\"");
+        assert_eq!(invalid_rows[0].values[0], "tx_0002");
+        assert_eq!(invalid_rows[1].row, Some(4));
+        assert_eq!(invalid_rows[1].message, "missing amount");
     }
 
     #[test]
@@ -180,7 +460,7 @@ mod tests {
 
         assert_eq!(result.len(), 3);
         assert_eq!(result[2].category, "category3".to_string());
-        assert_eq!(result[2].amount, -7.89);
+        assert_eq!(result[2].amount, Decimal::new(-789, 2));
     }
 
     #[test]
@@ -190,6 +470,52 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_parse_category_split_empty() {
+        let value = Some(Value::String("".to_string()));
+        let result = parse_category_split(value.as_ref()).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_check_category_split_total() {
+        let value = Some(Value::String("Groceries:-8.00,Household:-2.00".to_string()));
+        let mut transaction = Transaction {
+            id: "tx_0001".to_string(),
+            amount: Decimal::new(-1000, 2),
+            category_split: parse_category_split(value.as_ref()).unwrap(),
+            ..Default::default()
+        };
+
+        assert!(transaction.check_category_split().is_ok());
+
+        transaction.amount = Decimal::new(-1200, 2);
+        let result = transaction.check_category_split();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Category split of transaction tx_0001 totals -10.00, but the transaction amount is -12.00"
+        );
+    }
+
+    #[test]
+    fn test_check_category_split_total_in_currency_precision() {
+        let value = Some(Value::String("Groceries:-800,Household:-200".to_string()));
+        let transaction = Transaction {
+            id: "tx_0001".to_string(),
+            amount: Decimal::new(-1200, 0),
+            currency: "JPY".to_string(),
+            category_split: parse_category_split(value.as_ref()).unwrap(),
+            ..Default::default()
+        };
+
+        let result = transaction.check_category_split();
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Category split of transaction tx_0001 totals -1000, but the transaction amount is -1200"
+        );
+    }
+
     #[test]
     fn test_parse_category_split_err() {
         let value = Some(Value::String(
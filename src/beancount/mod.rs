@@ -8,12 +8,21 @@ pub mod datafile_paths;
 pub mod directive;
 pub mod generate;
 pub mod google;
+pub mod money;
+pub mod monzo;
+pub mod monzo_csv;
+pub mod parser;
+pub mod sanitise;
+pub mod source;
+pub mod store;
 pub mod transaction;
 pub mod user_settings;
 
 use std::path::PathBuf;
 
 use datafile_paths::{DataFilePaths, InitFlag};
+use google::GoogleApi;
+use monzo::MonzoAuth;
 use user_settings::UserSettings;
 
 use crate::error::AppError as Error;
@@ -23,6 +32,8 @@ use crate::error::AppError as Error;
 pub struct Beancount {
     pub data_file_paths: DataFilePaths,
     pub user_settings: UserSettings,
+    pub google_api: GoogleApi,
+    pub monzo_auth: MonzoAuth,
 }
 
 /// Constructors
@@ -34,11 +45,23 @@ impl Beancount {
         Ok(Self {
             data_file_paths,
             user_settings,
+            google_api: GoogleApi::default(),
+            monzo_auth: MonzoAuth::default(),
         })
     }
+
+    /// Use `google_api` to reach Google Sheets.
+    pub fn with_google_api(self, google_api: GoogleApi) -> Self {
+        Self { google_api, ..self }
+    }
+
+    /// Use `monzo_auth` to reach the Monzo API.
+    pub fn with_monzo_auth(self, monzo_auth: MonzoAuth) -> Self {
+        Self { monzo_auth, ..self }
+    }
 }
 
-/// Associated functions
+// Associated functions
 // impl Beancount {
 //     pub fn has_user_settings() -> bool {
 //         UserSettings::has_user_settings()
@@ -49,23 +72,20 @@ impl Beancount {
 
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use temp_dir::TempDir;
 
     use super::*;
 
     #[test]
     fn should_return_new_instance() {
-        // Determine which .env file to load
-        let env_file = env::var("ENV_FILE").unwrap_or_else(|_| ".env.dev".to_string());
-        let path = PathBuf::from(env_file);
-
-        // Load environment variables from the specified file
-        dotenvy::from_filename(path).expect("Failed to load .env file");
+        let data_dir = TempDir::new().unwrap();
+        std::fs::write(
+            data_dir.child("beancount.yaml"),
+            "start_date: \"2024-01-01\"\n",
+        )
+        .unwrap();
 
-        let data_dir = env::var("DATA_DIR")
-            .map(PathBuf::from)
-            .expect("Failed to get data_dir from .env");
-        let beancount = Beancount::with_data_dir(data_dir);
+        let beancount = Beancount::with_data_dir(data_dir.path().to_path_buf());
 
         assert!(beancount.is_ok());
     }
@@ -74,9 +94,10 @@ mod tests {
     fn should_return_configuration_error() {
         let data_dir = PathBuf::from("/tmp");
         let beancount = Beancount::with_data_dir(data_dir);
-        match beancount {
-            Err(Error::ConfigurationError(_)) => assert!(true),
-            _ => panic!("Expected ConfigError, got {:?}", beancount),
-        }
+        assert!(
+            matches!(beancount, Err(Error::ConfigurationError(_))),
+            "Expected ConfigError, got {:?}",
+            beancount
+        );
     }
 }
@@ -0,0 +1,72 @@
+//! Exact amounts of money.
+//!
+//! Amounts are held as a [`Decimal`] in major units, e.g. `12.34` for £12.34, so adding and
+//! negating them never drifts. The Monzo API and the transaction store count in minor units
+//! and convert at the boundary, using the currency's exponent from the ISO 4217 table: JPY has
+//! no minor unit and KWD has three decimal places.
+
+use rust_decimal::prelude::ToPrimitive;
+pub use rust_decimal::Decimal;
+use rusty_money::iso;
+
+use crate::error::AppError as Error;
+
+/// The number of decimal places assumed for currencies missing from the ISO table.
+const DEFAULT_EXPONENT: u32 = 2;
+
+/// The number of decimal places in a minor unit of `currency`, e.g. 2 for GBP, 0 for JPY.
+pub fn exponent(currency: &str) -> u32 {
+    iso::find(currency.trim())
+        .map(|c| c.exponent)
+        .unwrap_or(DEFAULT_EXPONENT)
+}
+
+/// The amount of `minor` units of `currency`, e.g. `1234` GBP -> `12.34`, `1234` JPY -> `1234`.
+pub fn from_minor(minor: i64, currency: &str) -> Decimal {
+    Decimal::new(minor, exponent(currency))
+}
+
+/// The number of minor units of `currency` in `amount`, e.g. `12.34` GBP -> `1234`, rounding any
+/// fraction of a minor unit to the nearest. Fails if the amount doesn't fit in an `i64`.
+pub fn to_minor(amount: Decimal, currency: &str) -> Result<i64, Error> {
+    let minor_units_per_major = Decimal::from(10_i64.pow(exponent(currency)));
+
+    amount
+        .checked_mul(minor_units_per_major)
+        .and_then(|minor| minor.round().to_i64())
+        .ok_or_else(|| Error::AmountRangeError(amount.to_string(), currency.to_string()))
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_minor_units_exactly() {
+        assert_eq!(from_minor(29, "GBP"), Decimal::new(29, 2));
+        assert_eq!(to_minor(Decimal::new(29, 2), "GBP").unwrap(), 29);
+        assert_eq!(to_minor(Decimal::new(-123456, 2), "GBP").unwrap(), -123456);
+        assert_eq!(to_minor(Decimal::new(5, 0), "GBP").unwrap(), 500);
+        assert_eq!(to_minor(Decimal::new(1235, 3), "GBP").unwrap(), 124);
+    }
+
+    #[test]
+    fn should_report_amount_too_large_for_minor_units() {
+        let result = to_minor(Decimal::MAX, "GBP");
+
+        assert!(matches!(result, Err(Error::AmountRangeError(_, _))));
+    }
+
+    #[test]
+    fn should_use_currency_exponent() {
+        assert_eq!(exponent("JPY"), 0);
+        assert_eq!(exponent("KWD"), 3);
+        assert_eq!(exponent("XYZ"), 2);
+
+        assert_eq!(from_minor(1500, "JPY"), Decimal::new(1500, 0));
+        assert_eq!(from_minor(1500, "KWD"), Decimal::new(1500, 3));
+        assert_eq!(to_minor(Decimal::new(1234, 3), "KWD").unwrap(), 1234);
+    }
+}
@@ -0,0 +1,57 @@
+//! Gets accounts from the Monzo API.
+
+use serde::Deserialize;
+
+use crate::error::AppError as Error;
+
+use super::MonzoClient;
+
+/// A struct to represent a Monzo account.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Account {
+    pub id: String,
+    pub description: String,
+    #[serde(default)]
+    pub closed: bool,
+    #[serde(rename = "type")]
+    pub account_type: Option<String>,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountsResponse {
+    accounts: Vec<Account>,
+}
+
+impl MonzoClient {
+    /// Get the accounts owned by the authenticated user.
+    pub async fn accounts(&mut self) -> Result<Vec<Account>, Error> {
+        let response: AccountsResponse = self.get("/accounts", &[]).await?;
+
+        Ok(response.accounts)
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::super::tests::{credentials_file, mock_server};
+    use super::*;
+
+    #[tokio::test]
+    async fn accounts() {
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+
+        let accounts = client.accounts().await.unwrap();
+
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].id, "acc_0001");
+        assert_eq!(accounts[0].currency, Some("GBP".to_string()));
+        assert!(accounts[1].closed);
+    }
+}
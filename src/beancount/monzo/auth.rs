@@ -0,0 +1,165 @@
+//! Handles Monzo OAuth2 credentials and access token refresh.
+
+use std::{fs, path::Path};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError as Error;
+
+use super::MonzoClient;
+
+/// Refresh tokens this long before they expire.
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// A struct to represent the OAuth2 client and token state persisted on disk.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Credentials {
+    pub client_id: String,
+    pub client_secret: String,
+    pub access_token: Option<String>,
+    pub refresh_token: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    expires_in: i64,
+}
+
+impl Credentials {
+    /// Read credentials from a JSON file.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let path_name = || path.to_string_lossy().to_string();
+        if !path.exists() {
+            return Err(Error::MonzoCredentialsMissing(path_name()));
+        }
+
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .map_err(|e| Error::MonzoCredentialsInvalid(path_name(), e.to_string()))
+    }
+
+    /// Write credentials to a JSON file.
+    pub fn to_file(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the access token is missing or about to expire.
+    pub fn is_expired(&self) -> bool {
+        match (&self.access_token, self.expires_at) {
+            (Some(_), Some(expires_at)) => {
+                expires_at <= Utc::now() + Duration::seconds(EXPIRY_MARGIN_SECS)
+            }
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+impl MonzoClient {
+    /// Exchange the refresh token for a new access token and persist the result.
+    pub(crate) async fn refresh(&mut self) -> Result<(), Error> {
+        let params = [
+            ("grant_type", "refresh_token"),
+            ("client_id", self.credentials.client_id.as_str()),
+            ("client_secret", self.credentials.client_secret.as_str()),
+            ("refresh_token", self.credentials.refresh_token.as_str()),
+        ];
+
+        let response = self
+            .http
+            .post(format!("{}/oauth2/token", self.base_url))
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Error::MonzoApiError(format!(
+                "Token refresh returned {}",
+                response.status()
+            )));
+        }
+
+        let token = response.json::<TokenResponse>().await?;
+
+        self.credentials.access_token = Some(token.access_token);
+        self.credentials.refresh_token = token.refresh_token;
+        self.credentials.expires_at = Some(Utc::now() + Duration::seconds(token.expires_in));
+        self.credentials.to_file(&self.credentials_file)?;
+
+        Ok(())
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::super::tests::mock_server;
+    use super::*;
+
+    #[tokio::test]
+    async fn should_refresh_expired_token() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let credentials_file = dir.child("monzo_credentials.json");
+        let credentials = Credentials {
+            client_id: "oauth2client_0001".to_string(),
+            client_secret: "secret".to_string(),
+            access_token: Some("stale".to_string()),
+            refresh_token: "refresh_0001".to_string(),
+            expires_at: Some(Utc::now() - Duration::hours(1)),
+        };
+        credentials.to_file(&credentials_file).unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file.clone()).unwrap();
+
+        // Act
+        let accounts = client.accounts().await.unwrap();
+
+        // Assert
+        assert!(!accounts.is_empty());
+        let persisted = Credentials::from_file(&credentials_file).unwrap();
+        assert_eq!(persisted.access_token, Some("access_0002".to_string()));
+        assert_eq!(persisted.refresh_token, "refresh_0002".to_string());
+        assert!(!persisted.is_expired());
+    }
+
+    #[test]
+    fn should_report_missing_credentials() {
+        let dir = TempDir::new().unwrap();
+
+        let result = MonzoClient::new("http://localhost", dir.child("monzo_credentials.json"));
+
+        assert!(matches!(result, Err(Error::MonzoCredentialsMissing(_))));
+    }
+
+    #[test]
+    fn should_report_invalid_credentials() {
+        let dir = TempDir::new().unwrap();
+        let credentials_file = dir.child("monzo_credentials.json");
+        fs::write(&credentials_file, "{}").unwrap();
+
+        let result = Credentials::from_file(&credentials_file);
+
+        assert!(matches!(result, Err(Error::MonzoCredentialsInvalid(_, _))));
+    }
+
+    #[test]
+    fn should_be_expired_without_access_token() {
+        let credentials = Credentials {
+            client_id: "oauth2client_0001".to_string(),
+            client_secret: "secret".to_string(),
+            access_token: None,
+            refresh_token: "refresh_0001".to_string(),
+            expires_at: None,
+        };
+        assert!(credentials.is_expired());
+    }
+}
@@ -0,0 +1,45 @@
+//! Gets the balance of a Monzo account.
+
+use serde::Deserialize;
+
+use crate::error::AppError as Error;
+
+use super::MonzoClient;
+
+/// A struct to represent the balance of a Monzo account in minor units.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Balance {
+    pub balance: i64,
+    pub total_balance: i64,
+    pub currency: String,
+}
+
+impl MonzoClient {
+    /// Get the balance of the account with id `account_id`.
+    pub async fn balance(&mut self, account_id: &str) -> Result<Balance, Error> {
+        self.get("/balance", &[("account_id", account_id.to_string())])
+            .await
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::super::tests::{credentials_file, mock_server};
+    use super::*;
+
+    #[tokio::test]
+    async fn balance() {
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+
+        let balance = client.balance("acc_0001").await.unwrap();
+
+        assert_eq!(balance.balance, 123456);
+        assert_eq!(balance.currency, "GBP");
+    }
+}
@@ -0,0 +1,373 @@
+//! Authenticates and accesses the [Monzo API](https://docs.monzo.com/).
+//!
+
+pub mod accounts;
+pub mod auth;
+pub mod balance;
+pub mod pots;
+pub mod transactions;
+
+use std::{collections::HashMap, path::PathBuf};
+
+use chrono::NaiveDate;
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::configuration::ApplicationSettings;
+use crate::error::AppError as Error;
+
+use super::google::transactions::Transaction;
+use super::source::{Pot, SourceAccount, TransactionSource, POT_TRANSFER};
+use auth::Credentials;
+
+const DEFAULT_BASE_URL: &str = "https://api.monzo.com";
+const CREDENTIALS_FILE_NAME: &str = "monzo_credentials.json";
+
+/// Where to find the credentials for the Monzo API.
+#[derive(Debug, Clone, Default)]
+pub struct MonzoAuth {
+    /// The file holding the OAuth2 client and token state.
+    pub credentials_file: PathBuf,
+}
+
+impl From<&ApplicationSettings> for MonzoAuth {
+    fn from(settings: &ApplicationSettings) -> Self {
+        MonzoAuth {
+            credentials_file: settings.secrets_dir.join(CREDENTIALS_FILE_NAME),
+        }
+    }
+}
+
+/// A struct to represent the Monzo API section of the user settings.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonzoApiSettings {
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    pub accounts: Vec<MonzoApiAccount>,
+}
+
+/// A struct to represent a Monzo account accessed through the API.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonzoApiAccount {
+    pub country: String,
+    pub institution: String,
+    pub name: String,
+    pub account_id: String,
+}
+
+fn default_base_url() -> String {
+    DEFAULT_BASE_URL.to_string()
+}
+
+impl From<&MonzoApiAccount> for SourceAccount {
+    fn from(account: &MonzoApiAccount) -> Self {
+        SourceAccount {
+            country: account.country.clone(),
+            institution: account.institution.clone(),
+            name: account.name.clone(),
+        }
+    }
+}
+
+/// An authenticated client for the Monzo API.
+pub struct MonzoClient {
+    http: reqwest::Client,
+    base_url: String,
+    credentials_file: PathBuf,
+    credentials: Credentials,
+}
+
+impl MonzoClient {
+    /// Create a client for `base_url` using the credentials stored in `credentials_file`.
+    pub fn new(base_url: &str, credentials_file: PathBuf) -> Result<Self, Error> {
+        let credentials = Credentials::from_file(&credentials_file)?;
+
+        Ok(MonzoClient {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            credentials_file,
+            credentials,
+        })
+    }
+
+    // Send an authenticated GET request, refreshing the access token if it has expired.
+    async fn get<T: DeserializeOwned>(
+        &mut self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, Error> {
+        if self.credentials.is_expired() {
+            self.refresh().await?;
+        }
+
+        let mut response = self.send_get(path, query).await?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            self.refresh().await?;
+            response = self.send_get(path, query).await?;
+        }
+
+        if !response.status().is_success() {
+            return Err(Error::MonzoApiError(format!(
+                "GET {} returned {}",
+                path,
+                response.status()
+            )));
+        }
+
+        Ok(response.json::<T>().await?)
+    }
+
+    async fn send_get(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<reqwest::Response, Error> {
+        let response = self
+            .http
+            .get(format!("{}{}", self.base_url, path))
+            .bearer_auth(self.credentials.access_token.clone().unwrap_or_default())
+            .query(query)
+            .send()
+            .await?;
+
+        Ok(response)
+    }
+}
+
+/// A struct for representing the transactions and pots of the configured Monzo API accounts.
+pub struct MonzoApi {
+    pub settings: MonzoApiSettings,
+    pub transactions: HashMap<String, Vec<Transaction>>,
+    pub pots: HashMap<String, Vec<Pot>>,
+}
+
+impl MonzoApi {
+    /// Create a MonzoApi instance, authenticating with the credentials `auth` points to.
+    pub async fn new(
+        settings: MonzoApiSettings,
+        auth: &MonzoAuth,
+        since: NaiveDate,
+    ) -> Result<Self, Error> {
+        let mut client = MonzoClient::new(&settings.base_url, auth.credentials_file.clone())?;
+
+        MonzoApi::with_client(&mut client, settings, since).await
+    }
+
+    /// Create a MonzoApi instance by fetching pots and transactions since `since` with `client`.
+    pub async fn with_client(
+        client: &mut MonzoClient,
+        settings: MonzoApiSettings,
+        since: NaiveDate,
+    ) -> Result<Self, Error> {
+        let mut transactions = HashMap::new();
+        let mut pots = HashMap::new();
+
+        for account in &settings.accounts {
+            let account_pots: Vec<Pot> = client
+                .pots(&account.account_id)
+                .await?
+                .iter()
+                .map(Pot::from)
+                .collect();
+            let mut account_transactions = client.transactions(&account.account_id, since).await?;
+            resolve_pot_names(&mut account_transactions, &account_pots);
+
+            transactions.insert(account.account_id.clone(), account_transactions);
+            pots.insert(account.account_id.clone(), account_pots);
+        }
+
+        Ok(MonzoApi {
+            settings,
+            transactions,
+            pots,
+        })
+    }
+
+    fn account_id(&self, account: &SourceAccount) -> Option<&String> {
+        self.settings
+            .accounts
+            .iter()
+            .find(|a| &SourceAccount::from(*a) == account)
+            .map(|a| &a.account_id)
+    }
+}
+
+// Replace pot ids with pot names, naming pot transfers after their pot.
+fn resolve_pot_names(transactions: &mut [Transaction], pots: &[Pot]) {
+    for tx in transactions.iter_mut() {
+        let pot = match &tx.pot {
+            Some(pot_id) => pots.iter().find(|p| &p.id == pot_id),
+            None => continue,
+        };
+
+        if let Some(pot) = pot {
+            tx.pot = Some(pot.name.clone());
+            if tx.payment_type == POT_TRANSFER {
+                tx.name = pot.name.clone();
+            }
+        }
+    }
+}
+
+impl TransactionSource for MonzoApi {
+    fn label(&self) -> String {
+        self.settings.base_url.clone()
+    }
+
+    fn accounts(&self) -> Vec<SourceAccount> {
+        self.settings
+            .accounts
+            .iter()
+            .map(SourceAccount::from)
+            .collect()
+    }
+
+    fn transactions(&self, account: &SourceAccount) -> &[Transaction] {
+        self.account_id(account)
+            .and_then(|id| self.transactions.get(id))
+            .map(|t| t.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn pots(&self, account: &SourceAccount) -> Vec<Pot> {
+        self.account_id(account)
+            .and_then(|id| self.pots.get(id))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fs;
+
+    use temp_dir::TempDir;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    pub(crate) const ACCOUNTS: &str = include_str!("../../../tests/fixtures/monzo/accounts.json");
+    pub(crate) const TRANSACTIONS: &str =
+        include_str!("../../../tests/fixtures/monzo/transactions.json");
+    pub(crate) const POTS: &str = include_str!("../../../tests/fixtures/monzo/pots.json");
+    pub(crate) const BALANCE: &str = include_str!("../../../tests/fixtures/monzo/balance.json");
+    pub(crate) const TOKEN: &str = include_str!("../../../tests/fixtures/monzo/token.json");
+
+    /// Start a mock Monzo API serving the recorded fixtures.
+    pub(crate) async fn mock_server() -> MockServer {
+        let server = MockServer::start().await;
+
+        for (route, body) in [
+            ("/accounts", ACCOUNTS),
+            ("/transactions", TRANSACTIONS),
+            ("/pots", POTS),
+            ("/balance", BALANCE),
+        ] {
+            Mock::given(method("GET"))
+                .and(path(route))
+                .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+                .mount(&server)
+                .await;
+        }
+
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(TOKEN, "application/json"))
+            .mount(&server)
+            .await;
+
+        server
+    }
+
+    /// Write a credentials file holding a valid access token.
+    pub(crate) fn credentials_file(dir: &TempDir) -> PathBuf {
+        let credentials_file = dir.child(CREDENTIALS_FILE_NAME);
+        fs::write(
+            &credentials_file,
+            r#"{
+  "client_id": "oauth2client_0001",
+  "client_secret": "secret",
+  "access_token": "access_0001",
+  "refresh_token": "refresh_0001",
+  "expires_at": "2999-01-01T00:00:00Z"
+}"#,
+        )
+        .unwrap();
+
+        credentials_file
+    }
+
+    #[tokio::test]
+    async fn should_provide_transactions_per_account() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+        let settings = MonzoApiSettings {
+            base_url: server.uri(),
+            accounts: vec![MonzoApiAccount {
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                name: "Personal".to_string(),
+                account_id: "acc_0001".to_string(),
+            }],
+        };
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        // Act
+        let api = MonzoApi::with_client(&mut client, settings, since)
+            .await
+            .unwrap();
+
+        // Assert
+        let accounts = api.accounts();
+        assert_eq!(accounts.len(), 1);
+        let transactions = api.transactions(&accounts[0]);
+        assert_eq!(transactions.len(), 4);
+        assert_eq!(api.categories(&accounts[0]), vec!["Bills", "Groceries"]);
+    }
+
+    #[tokio::test]
+    async fn should_name_pot_transactions() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+        let settings = MonzoApiSettings {
+            base_url: server.uri(),
+            accounts: vec![MonzoApiAccount {
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                name: "Personal".to_string(),
+                account_id: "acc_0001".to_string(),
+            }],
+        };
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        // Act
+        let api = MonzoApi::with_client(&mut client, settings, since)
+            .await
+            .unwrap();
+
+        // Assert
+        let account = &api.accounts()[0];
+        let transactions = api.transactions(account);
+        assert_eq!(transactions[0].pot, Some("Car Tax".to_string()));
+        assert_eq!(transactions[0].name, "DVLA");
+        assert_eq!(transactions[2].pot, Some("Holiday".to_string()));
+        assert_eq!(transactions[2].name, "Holiday");
+
+        let pots = api.pots(account);
+        assert_eq!(pots.len(), 2);
+        assert_eq!(
+            pots[1].closed,
+            Some(NaiveDate::from_ymd_opt(2024, 5, 20).unwrap())
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Gets the pots of a Monzo account.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::beancount::source::Pot as SourcePot;
+use crate::error::AppError as Error;
+
+use super::MonzoClient;
+
+/// A struct to represent a Monzo pot.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pot {
+    pub id: String,
+    pub name: String,
+    pub balance: i64,
+    pub currency: String,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PotsResponse {
+    pots: Vec<Pot>,
+}
+
+impl From<&Pot> for SourcePot {
+    fn from(pot: &Pot) -> Self {
+        SourcePot {
+            id: pot.id.clone(),
+            name: pot.name.clone(),
+            currency: pot.currency.clone(),
+            opened: pot.created.date_naive(),
+            closed: pot.deleted.then(|| pot.updated.date_naive()),
+        }
+    }
+}
+
+impl MonzoClient {
+    /// Get the pots belonging to the account with id `account_id`.
+    pub async fn pots(&mut self, account_id: &str) -> Result<Vec<Pot>, Error> {
+        let response: PotsResponse = self
+            .get("/pots", &[("current_account_id", account_id.to_string())])
+            .await?;
+
+        Ok(response.pots)
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use temp_dir::TempDir;
+
+    use super::super::tests::{credentials_file, mock_server};
+    use super::*;
+
+    #[tokio::test]
+    async fn pots() {
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+
+        let pots = client.pots("acc_0001").await.unwrap();
+
+        assert_eq!(pots.len(), 2);
+        assert_eq!(pots[0].name, "Holiday");
+        assert!(pots[1].deleted);
+    }
+
+    #[tokio::test]
+    async fn deleted_pot_closes_when_updated() {
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+
+        let pots = client.pots("acc_0001").await.unwrap();
+        let open = SourcePot::from(&pots[0]);
+        let deleted = SourcePot::from(&pots[1]);
+
+        assert_eq!(open.opened, NaiveDate::from_ymd_opt(2024, 1, 10).unwrap());
+        assert_eq!(open.closed, None);
+        assert_eq!(
+            deleted.closed,
+            Some(NaiveDate::from_ymd_opt(2024, 5, 20).unwrap())
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Get transactions from the Monzo API.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::beancount::google::transactions::{CategorySplit, Transaction};
+use crate::beancount::money::from_minor;
+use crate::error::AppError as Error;
+
+use super::MonzoClient;
+
+/// The maximum page size accepted by the transactions endpoint.
+const PAGE_LIMIT: usize = 100;
+
+/// A struct to represent a transaction as returned by the Monzo API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiTransaction {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    pub description: String,
+    pub amount: i64,
+    pub currency: String,
+    pub local_amount: i64,
+    pub local_currency: String,
+    pub category: String,
+    #[serde(default)]
+    pub categories: Option<BTreeMap<String, i64>>,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub scheme: Option<String>,
+    #[serde(default)]
+    pub merchant: Option<Value>,
+    #[serde(default)]
+    pub counterparty: Option<Value>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub decline_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionsResponse {
+    transactions: Vec<ApiTransaction>,
+}
+
+impl MonzoClient {
+    /// Get the raw transactions for `account_id` created on or after `since`.
+    pub async fn api_transactions(
+        &mut self,
+        account_id: &str,
+        since: NaiveDate,
+    ) -> Result<Vec<ApiTransaction>, Error> {
+        let mut transactions: Vec<ApiTransaction> = Vec::new();
+        let mut cursor = since
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is a valid time")
+            .and_utc()
+            .to_rfc3339();
+
+        loop {
+            let query = [
+                ("account_id", account_id.to_string()),
+                ("since", cursor.clone()),
+                ("limit", PAGE_LIMIT.to_string()),
+                ("expand[]", "merchant".to_string()),
+            ];
+            let response: TransactionsResponse = self.get("/transactions", &query).await?;
+            let page_len = response.transactions.len();
+
+            if let Some(last) = response.transactions.last() {
+                cursor = last.id.clone();
+            }
+            transactions.extend(response.transactions);
+
+            if page_len < PAGE_LIMIT {
+                break;
+            }
+        }
+
+        Ok(transactions)
+    }
+
+    /// Get the settled and pending transactions for `account_id` created on or after `since`.
+    pub async fn transactions(
+        &mut self,
+        account_id: &str,
+        since: NaiveDate,
+    ) -> Result<Vec<Transaction>, Error> {
+        let transactions = self
+            .api_transactions(account_id, since)
+            .await?
+            .iter()
+            .filter(|t| t.decline_reason.is_none())
+            .map(Transaction::from)
+            .collect();
+
+        Ok(transactions)
+    }
+}
+
+impl From<&ApiTransaction> for Transaction {
+    fn from(tx: &ApiTransaction) -> Self {
+        Transaction {
+            id: tx.id.clone(),
+            date: tx.created.date_naive(),
+            payment_type: payment_type(tx.scheme.as_deref()),
+            name: transaction_name(tx),
+            category: category_name(&tx.category),
+            amount: from_minor(tx.amount, &tx.currency),
+            currency: tx.currency.clone(),
+            local_amount: from_minor(tx.local_amount, &tx.local_currency),
+            local_currency: tx.local_currency.clone(),
+            notes: Some(tx.notes.clone()).filter(|n| !n.is_empty()),
+            description: Some(tx.description.clone()).filter(|d| !d.is_empty()),
+            category_split: category_split(tx),
+            // the pot id, resolved to the pot name once the account's pots are known
+            pot: tx.metadata.get("pot_id").cloned(),
+            sheet: None,
+        }
+    }
+}
+
+// Map the API payment scheme onto the type names used by the Monzo export.
+fn payment_type(scheme: Option<&str>) -> String {
+    match scheme {
+        Some("uk_retail_pot") => "Pot transfer",
+        Some("mastercard") => "Card payment",
+        Some("payport_faster_payments") => "Faster payment",
+        Some("bacs") => "Direct Debit",
+        Some("p2p_payment") => "Monzo-to-Monzo",
+        Some(other) => other,
+        None => "",
+    }
+    .to_string()
+}
+
+// The merchant or counterparty name, falling back to the description.
+fn transaction_name(tx: &ApiTransaction) -> String {
+    [&tx.merchant, &tx.counterparty]
+        .iter()
+        .filter_map(|v| v.as_ref())
+        .filter_map(|v| v.get("name").and_then(Value::as_str))
+        .find(|name| !name.is_empty())
+        .unwrap_or(&tx.description)
+        .to_string()
+}
+
+// e.g. "eating_out" -> "Eating out"
+fn category_name(category: &str) -> String {
+    let category = category.replace('_', " ");
+    let mut chars = category.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn category_split(tx: &ApiTransaction) -> Option<Vec<CategorySplit>> {
+    let categories = tx.categories.as_ref().filter(|c| c.len() > 1)?;
+
+    Some(
+        categories
+            .iter()
+            .map(|(category, amount)| CategorySplit {
+                category: category_name(category),
+                amount: from_minor(*amount, &tx.currency),
+            })
+            .collect(),
+    )
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::super::tests::{credentials_file, mock_server};
+    use super::*;
+
+    #[tokio::test]
+    async fn transactions() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let mut client = MonzoClient::new(&server.uri(), credentials_file(&dir)).unwrap();
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        // Act
+        let transactions = client.transactions("acc_0001", since).await.unwrap();
+
+        // Assert
+        assert_eq!(transactions.len(), 4);
+
+        let pot_paid = &transactions[0];
+        assert_eq!(pot_paid.payment_type, "Direct Debit");
+        assert_eq!(pot_paid.category, "Bills");
+        assert_eq!(pot_paid.pot, Some("pot_0002".to_string()));
+
+        let card = &transactions[1];
+        assert_eq!(card.id, "tx_0001");
+        assert_eq!(card.date, NaiveDate::from_ymd_opt(2024, 6, 2).unwrap());
+        assert_eq!(card.payment_type, "Card payment");
+        assert_eq!(card.name, "Tesco");
+        assert_eq!(card.category, "Groceries");
+        assert_eq!(card.amount, from_minor(-1234, "GBP"));
+        assert_eq!(card.notes, None);
+
+        let pot = &transactions[2];
+        assert_eq!(pot.payment_type, "Pot transfer");
+        assert_eq!(pot.category, "Transfers");
+        assert_eq!(pot.pot, Some("pot_0001".to_string()));
+
+        let income = &transactions[3];
+        assert_eq!(income.name, "Acme Ltd");
+        assert_eq!(income.category, "Income");
+        assert_eq!(income.notes, Some("June salary".to_string()));
+    }
+
+    #[test]
+    fn should_name_category() {
+        assert_eq!(category_name("eating_out"), "Eating out");
+        assert_eq!(category_name("groceries"), "Groceries");
+        assert_eq!(category_name(""), "");
+    }
+}
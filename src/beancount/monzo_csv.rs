@@ -0,0 +1,250 @@
+//! Reads transactions from Monzo's in-app "Export statement" CSV files.
+//!
+//! The export has the same columns as the Monzo Google Sheet integration, so rows are
+//! parsed into the same `Transaction` structs as a `GoogleSheet` produces. Every `.csv`
+//! file in the configured directory is read, and transactions appearing in more than one
+//! export are included once.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::AppError as Error;
+
+use super::google::columns::ColumnMapping;
+use super::google::transactions::{parse_rows, Transaction};
+use super::source::{InvalidRow, SourceAccount, TransactionSource};
+
+/// A struct to represent an account read from Monzo CSV exports.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonzoCsvAccount {
+    pub country: String,
+    pub institution: String,
+    pub name: String,
+    /// Directory containing the exports, relative to the data directory if not absolute.
+    pub directory: PathBuf,
+    /// Header names of the transaction columns, where they differ from the Monzo defaults.
+    #[serde(default)]
+    pub columns: ColumnMapping,
+}
+
+/// A struct for representing the transactions in a set of Monzo CSV exports.
+pub struct MonzoCsv {
+    pub account: MonzoCsvAccount,
+    pub transactions: Vec<Transaction>,
+    pub invalid_rows: Vec<InvalidRow>,
+}
+
+impl From<&MonzoCsvAccount> for SourceAccount {
+    fn from(account: &MonzoCsvAccount) -> Self {
+        SourceAccount {
+            country: account.country.clone(),
+            institution: account.institution.clone(),
+            name: account.name.clone(),
+        }
+    }
+}
+
+impl MonzoCsv {
+    /// Load the exports for `account`, resolving its directory against `data_dir`.
+    pub fn new(account: MonzoCsvAccount, data_dir: &Path) -> Result<Self, Error> {
+        let directory = data_dir.join(&account.directory);
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut transactions: Vec<Transaction> = Vec::new();
+        let mut invalid_rows: Vec<InvalidRow> = Vec::new();
+
+        for csv_file in get_csv_files(&directory)? {
+            let (file_transactions, file_invalid_rows) =
+                load_transactions(&csv_file, &account.columns)?;
+            invalid_rows.extend(file_invalid_rows);
+
+            for transaction in file_transactions {
+                if seen.insert(transaction.id.clone()) {
+                    transactions.push(transaction);
+                }
+            }
+        }
+
+        transactions.sort_by_key(|t| t.date);
+
+        Ok(MonzoCsv {
+            account,
+            transactions,
+            invalid_rows,
+        })
+    }
+}
+
+impl TransactionSource for MonzoCsv {
+    fn label(&self) -> String {
+        self.account.directory.to_string_lossy().to_string()
+    }
+
+    fn accounts(&self) -> Vec<SourceAccount> {
+        vec![SourceAccount::from(&self.account)]
+    }
+
+    fn transactions(&self, account: &SourceAccount) -> &[Transaction] {
+        if account != &SourceAccount::from(&self.account) {
+            return &[];
+        }
+
+        &self.transactions
+    }
+
+    fn invalid_rows(&self) -> &[InvalidRow] {
+        &self.invalid_rows
+    }
+}
+
+// get the .csv files in `dir`, sorted by name
+fn get_csv_files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut csv_files = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_file() && path.extension().and_then(std::ffi::OsStr::to_str) == Some("csv") {
+            csv_files.push(path);
+        }
+    }
+
+    csv_files.sort();
+
+    Ok(csv_files)
+}
+
+fn load_transactions(
+    csv_file: &Path,
+    columns: &ColumnMapping,
+) -> Result<(Vec<Transaction>, Vec<InvalidRow>), Error> {
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(csv_file)
+        .map_err(|e| Error::ApplicationError(format!("{}: {}", csv_file.display(), e)))?;
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| Error::ApplicationError(format!("{}: {}", csv_file.display(), e)))?
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    let sheet = csv_file.display().to_string();
+    let index = columns.index(&sheet, &headers)?;
+
+    let mut rows: Vec<Vec<Value>> = Vec::new();
+
+    for record in reader.records() {
+        let record = record
+            .map_err(|e| Error::ApplicationError(format!("{}: {}", csv_file.display(), e)))?;
+        rows.push(
+            record
+                .iter()
+                .map(|field| Value::String(field.to_string()))
+                .collect(),
+        );
+    }
+
+    Ok(parse_rows(&rows, &index, &sheet))
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use temp_dir::TempDir;
+
+    use crate::beancount::money::Decimal;
+
+    use super::*;
+
+    const HEADER: &str = "Transaction ID,Date,Time,Type,Name,Emoji,Category,Amount,Currency,Local amount,Local currency,Notes and #tags,Address,Receipt,Description,Category split,Money Out,Money In\n";
+
+    fn test_account() -> MonzoCsvAccount {
+        MonzoCsvAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+            directory: PathBuf::from("exports"),
+            columns: ColumnMapping::default(),
+        }
+    }
+
+    #[test]
+    fn should_load_export() {
+        // Arrange
+        let data_dir = TempDir::new().unwrap();
+        fs::create_dir(data_dir.child("exports")).unwrap();
+        fs::write(
+            data_dir.child("exports").join("statement.csv"),
+            format!(
+                "{HEADER}tx_0001,02/06/2024,09:15:00,Card payment,Tesco,,Groceries,-12.34,GBP,-12.34,GBP,Weekly shop,,,TESCO STORES,,-12.34,\n"
+            ),
+        )
+        .unwrap();
+
+        // Act
+        let source = MonzoCsv::new(test_account(), data_dir.path()).unwrap();
+
+        // Assert
+        let account = SourceAccount::from(&test_account());
+        let transactions = source.transactions(&account);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].id, "tx_0001");
+        assert_eq!(
+            transactions[0].date,
+            NaiveDate::from_ymd_opt(2024, 6, 2).unwrap()
+        );
+        assert_eq!(transactions[0].name, "Tesco");
+        assert_eq!(transactions[0].category, "Groceries");
+        assert_eq!(transactions[0].amount, Decimal::new(-1234, 2));
+        assert_eq!(transactions[0].notes, Some("Weekly shop".to_string()));
+        assert_eq!(
+            transactions[0].description,
+            Some("TESCO STORES".to_string())
+        );
+        assert!(transactions[0].category_split.is_none());
+    }
+
+    #[test]
+    fn should_merge_overlapping_exports() {
+        // Arrange
+        let data_dir = TempDir::new().unwrap();
+        fs::create_dir(data_dir.child("exports")).unwrap();
+        fs::write(
+            data_dir.child("exports").join("may.csv"),
+            format!(
+                "{HEADER}tx_0002,03/06/2024,10:00:00,Card payment,Cafe,,Eating out,-3.50,GBP,-3.50,GBP,,,,CAFE,,-3.50,\ntx_0001,02/06/2024,09:15:00,Card payment,Tesco,,Groceries,-12.34,GBP,-12.34,GBP,,,,TESCO,,-12.34,\n"
+            ),
+        )
+        .unwrap();
+        fs::write(
+            data_dir.child("exports").join("june.csv"),
+            format!(
+                "{HEADER}tx_0002,03/06/2024,10:00:00,Card payment,Cafe,,Eating out,-3.50,GBP,-3.50,GBP,,,,CAFE,,-3.50,\n"
+            ),
+        )
+        .unwrap();
+
+        // Act
+        let source = MonzoCsv::new(test_account(), data_dir.path()).unwrap();
+
+        // Assert
+        let account = SourceAccount::from(&test_account());
+        let ids: Vec<&str> = source
+            .transactions(&account)
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["tx_0001", "tx_0002"]);
+        assert_eq!(source.categories(&account), vec!["Eating out", "Groceries"]);
+    }
+}
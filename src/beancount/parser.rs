@@ -0,0 +1,788 @@
+//! Parses Beancount files into [`Directive`] values.
+//!
+//! The parser reads the parts of the [Beancount grammar](https://beancount.github.io/docs/beancount_language_syntax.html)
+//! that a `Directive` can represent: options, includes, `*` section headings, transactions, and
+//! open, close, balance, pad, note, document, event, price, commodity, query and custom
+//! directives. A posting without an amount is kept elided. A `;` comment on the line directly
+//! before an open, close or transaction becomes its comment.
+//!
+//! Metadata is kept, with every value as text, on transactions, postings and the directives
+//! added for other tools (pad through custom). Transaction payees, tags and links are kept.
+//! Price annotations are kept as a total price, so `@` per-unit prices are multiplied out.
+//! Transactions flagged `!` keep the flag and any other flag is read as `*`. Posting costs are
+//! accepted but not kept, and `plugin`, `pushtag` and `poptag` lines are skipped.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::{fs, path::Path};
+
+use chrono::NaiveDate;
+
+use crate::error::AppError as Error;
+
+use super::account::{Account, AccountType};
+use super::directive::{CustomValue, Directive};
+use super::money::Decimal;
+use super::transaction::{Flag, Metadata, Posting, TotalPrice, Transaction};
+
+/// Parse the directives in the Beancount file at `path`.
+pub fn parse_file(path: &Path) -> Result<Vec<Directive>, Error> {
+    let input = fs::read_to_string(path)?;
+
+    parse(&input)
+}
+
+/// Parse the directives in Beancount source text.
+pub fn parse(input: &str) -> Result<Vec<Directive>, Error> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut directives: Vec<Directive> = Vec::new();
+    let mut comment: Option<String> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line_number = i + 1;
+        let line = lines[i];
+        i += 1;
+
+        // the indented lines that follow belong to this line's directive
+        let body_start = i;
+        while i < lines.len() && is_body_line(lines[i]) {
+            i += 1;
+        }
+        let body = &lines[body_start..i];
+
+        let error = |message: String| Error::BeancountParseError(line_number, message);
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            comment = None;
+            continue;
+        }
+
+        if let Some(text) = line.trim_start().strip_prefix(';') {
+            comment = Some(text.strip_prefix(' ').unwrap_or(text).to_string());
+            continue;
+        }
+
+        if trimmed.starts_with('*') {
+            let heading = trimmed.trim_start_matches('*').trim();
+            directives.push(Directive::Comment(heading.to_string()));
+            comment = None;
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            return Err(error("indented line outside a directive".to_string()));
+        }
+
+        let tokens = tokenize(line).map_err(error)?;
+        let directive = parse_directive(&tokens, body, comment.take(), line_number)?;
+        directives.extend(directive);
+    }
+
+    Ok(directives)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Word(String),
+}
+
+// An indented, non-blank line continuing the directive above it.
+fn is_body_line(line: &str) -> bool {
+    line.starts_with(char::is_whitespace) && !line.trim().is_empty()
+}
+
+// Parse a top-level line and its body, returning `None` for lines that aren't represented.
+fn parse_directive(
+    tokens: &[Token],
+    body: &[&str],
+    comment: Option<String>,
+    line_number: usize,
+) -> Result<Option<Directive>, Error> {
+    let error = |message: String| Error::BeancountParseError(line_number, message);
+
+    let keyword = match tokens.first() {
+        Some(Token::Word(word)) => word.as_str(),
+        _ => return Err(error("expected a date or keyword".to_string())),
+    };
+
+    match keyword {
+        "option" => match &tokens[1..] {
+            [Token::Str(key), Token::Str(value), ..] => {
+                Ok(Some(Directive::Option(key.clone(), value.clone())))
+            }
+            _ => Err(error("option needs a name and a value".to_string())),
+        },
+        "include" => match &tokens[1..] {
+            [Token::Str(file), ..] => Ok(Some(Directive::Include(file.clone()))),
+            _ => Err(error("include needs a file name".to_string())),
+        },
+        "plugin" | "pushtag" | "poptag" => Ok(None),
+        _ => {
+            let date = parse_date(keyword).map_err(error)?;
+            let kind = match tokens.get(1) {
+                Some(Token::Word(kind)) => kind.as_str(),
+                _ => return Err(error("expected a directive after the date".to_string())),
+            };
+
+            parse_dated_directive(date, kind, &tokens[2..], body, comment, line_number)
+        }
+    }
+}
+
+fn parse_dated_directive(
+    date: NaiveDate,
+    kind: &str,
+    args: &[Token],
+    body: &[&str],
+    comment: Option<String>,
+    line_number: usize,
+) -> Result<Option<Directive>, Error> {
+    let error = |message: String| Error::BeancountParseError(line_number, message);
+    let metadata = || parse_metadata(body, line_number);
+    // open and close comments are written as sentences
+    let sentence = |c: String| c.strip_suffix('.').map(str::to_string).unwrap_or(c);
+
+    match kind {
+        "open" => {
+            let mut account = Account::from_str(word(args, 0).map_err(error)?).map_err(error)?;
+            let currencies: Vec<String> = match args.get(1) {
+                Some(Token::Word(currencies)) => {
+                    currencies.split(',').map(str::to_string).collect()
+                }
+                _ => vec![],
+            };
+            // an equity account's currency isn't part of its name
+            if let (AccountType::Equity, Some(currency)) =
+                (&account.account_type, currencies.first())
+            {
+                account.country = currency.clone();
+            }
+            let other_currencies = currencies
+                .into_iter()
+                .filter(|currency| *currency != account.country)
+                .collect();
+            Ok(Some(Directive::Open(
+                date,
+                account,
+                other_currencies,
+                comment.map(sentence),
+            )))
+        }
+        "close" => {
+            let account = Account::from_str(word(args, 0).map_err(error)?).map_err(error)?;
+            Ok(Some(Directive::Close(date, account, comment.map(sentence))))
+        }
+        "balance" => {
+            let account = Account::from_str(word(args, 0).map_err(error)?).map_err(error)?;
+            let amount = parse_number(word(args, 1).map_err(error)?).map_err(error)?;
+            let currency = word(args, 2).map_err(error)?;
+            Ok(Some(Directive::Balance(
+                date,
+                account,
+                amount,
+                currency.to_string(),
+            )))
+        }
+        "price" => {
+            let commodity = word(args, 0).map_err(error)?;
+            let price = parse_number(word(args, 1).map_err(error)?).map_err(error)?;
+            let currency = word(args, 2).map_err(error)?;
+            Ok(Some(Directive::Price(
+                date,
+                commodity.to_string(),
+                price,
+                currency.to_string(),
+                metadata()?,
+            )))
+        }
+        "pad" => {
+            let account = Account::from_str(word(args, 0).map_err(error)?).map_err(error)?;
+            let source = Account::from_str(word(args, 1).map_err(error)?).map_err(error)?;
+            Ok(Some(Directive::Pad(date, account, source, metadata()?)))
+        }
+        "note" => {
+            let account = Account::from_str(word(args, 0).map_err(error)?).map_err(error)?;
+            let note = string(args, 1).map_err(error)?;
+            Ok(Some(Directive::Note(date, account, note, metadata()?)))
+        }
+        "document" => {
+            let account = Account::from_str(word(args, 0).map_err(error)?).map_err(error)?;
+            let path = string(args, 1).map_err(error)?;
+            Ok(Some(Directive::Document(date, account, path, metadata()?)))
+        }
+        "event" => {
+            let event = string(args, 0).map_err(error)?;
+            let value = string(args, 1).map_err(error)?;
+            Ok(Some(Directive::Event(date, event, value, metadata()?)))
+        }
+        "commodity" => {
+            let commodity = word(args, 0).map_err(error)?;
+            Ok(Some(Directive::Commodity(
+                date,
+                commodity.to_string(),
+                metadata()?,
+            )))
+        }
+        "query" => {
+            let name = string(args, 0).map_err(error)?;
+            let query = string(args, 1).map_err(error)?;
+            Ok(Some(Directive::Query(date, name, query, metadata()?)))
+        }
+        "custom" => {
+            let custom = string(args, 0).map_err(error)?;
+            let values = parse_custom_values(&args[1..]).map_err(error)?;
+            Ok(Some(Directive::Custom(date, custom, values, metadata()?)))
+        }
+        // "txn" or a flag such as "*" or "!"
+        kind if kind == "txn" || (kind.len() == 1 && !kind.starts_with(char::is_alphanumeric)) => {
+            let flag = match kind {
+                "!" => Flag::Incomplete,
+                _ => Flag::Complete,
+            };
+            let transaction = parse_transaction(date, flag, args, body, comment, line_number)?;
+            Ok(Some(Directive::Transaction(Box::new(transaction))))
+        }
+        other => Err(error(format!("unsupported directive \"{}\"", other))),
+    }
+}
+
+fn parse_transaction(
+    date: NaiveDate,
+    flag: Flag,
+    args: &[Token],
+    body: &[&str],
+    comment: Option<String>,
+    line_number: usize,
+) -> Result<Transaction, Error> {
+    // the narration is the last string, after an optional payee; tags and links follow
+    let strings: Vec<&String> = args
+        .iter()
+        .filter_map(|t| match t {
+            Token::Str(s) => Some(s),
+            Token::Word(_) => None,
+        })
+        .collect();
+    let (payee, narration) = match strings.as_slice() {
+        [] => (None, String::new()),
+        [narration] => (None, narration.to_string()),
+        [.., payee, narration] => (Some(payee.to_string()), narration.to_string()),
+    };
+    let marked = |mark: char| -> BTreeSet<String> {
+        args.iter()
+            .filter_map(|t| match t {
+                Token::Word(w) => w.strip_prefix(mark).map(str::to_string),
+                Token::Str(_) => None,
+            })
+            .collect()
+    };
+
+    let mut metadata = Metadata::new();
+    let mut postings: Vec<Posting> = Vec::new();
+    // the indentation of the last posting, to tell its metadata from the transaction's
+    let mut posting_indent = 0;
+
+    for (offset, line) in body.iter().enumerate() {
+        let error = |message: String| Error::BeancountParseError(line_number + offset + 1, message);
+        let tokens = tokenize(line).map_err(error)?;
+
+        let mut words = tokens.iter().filter_map(|t| match t {
+            Token::Word(w) => Some(w.as_str()),
+            Token::Str(_) => None,
+        });
+
+        let first = match tokens.first() {
+            Some(Token::Word(first)) => first.as_str(),
+            // comment-only lines
+            None => continue,
+            Some(Token::Str(_)) => return Err(error("expected a posting".to_string())),
+        };
+
+        // metadata, e.g. `monzo-id: "tx_0001"`, belongs to the posting above when indented
+        // further than it
+        if let Some((key, value)) = metadata_entry(&tokens) {
+            match postings.last_mut() {
+                Some(posting) if indent(line) > posting_indent => {
+                    posting.metadata.insert(key, value);
+                }
+                _ => {
+                    metadata.insert(key, value);
+                }
+            }
+            continue;
+        }
+
+        // an optional posting flag
+        let account = match first {
+            "*" | "!" => {
+                words.next();
+                words.next().unwrap_or_default()
+            }
+            _ => words.next().unwrap_or_default(),
+        };
+        let account = Account::from_str(account).map_err(error)?;
+
+        let (amount, currency) = match (words.next(), words.next()) {
+            (Some(number), Some(currency)) if !number.starts_with(['@', '{']) => (
+                Some(parse_number(number).map_err(error)?),
+                Some(currency.to_string()),
+            ),
+            (Some(number), None) if !number.starts_with(['@', '{']) => {
+                return Err(error(format!("amount {} has no currency", number)))
+            }
+            _ => (None, None),
+        };
+
+        // a price annotation, after any cost, e.g. `@@ 15.60 USD` or `@ 1.26 USD`
+        let rest: Vec<&str> = words.collect();
+        let price = match rest.iter().position(|w| *w == "@@" || *w == "@") {
+            Some(at) => {
+                let (Some(number), Some(price_currency)) = (rest.get(at + 1), rest.get(at + 2))
+                else {
+                    return Err(error("price has no amount and currency".to_string()));
+                };
+                let number = parse_number(number).map_err(error)?;
+                let total = match (rest[at], amount) {
+                    ("@", Some(amount)) => number * amount,
+                    _ => number,
+                };
+                Some(TotalPrice {
+                    amount: total.abs(),
+                    currency: price_currency.to_string(),
+                })
+            }
+            None => None,
+        };
+
+        posting_indent = indent(line);
+        postings.push(Posting {
+            account,
+            amount,
+            currency: currency.unwrap_or_default(),
+            price,
+            metadata: Metadata::new(),
+        });
+    }
+
+    let transaction = Transaction {
+        date,
+        flag,
+        comment,
+        payee,
+        narration,
+        tags: marked('#'),
+        links: marked('^'),
+        metadata,
+        postings,
+    };
+
+    transaction
+        .check_postings()
+        .map_err(|message| Error::BeancountParseError(line_number, message))?;
+
+    Ok(transaction)
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ';' {
+            break;
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('\\') => match chars.next() {
+                        Some(escaped) => text.push(escaped),
+                        None => return Err("unterminated string".to_string()),
+                    },
+                    Some('"') => break,
+                    Some(c) => text.push(c),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+            tokens.push(Token::Str(text));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' || c == ';' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    Ok(tokens)
+}
+
+// The metadata lines in the body of a directive other than a transaction.
+fn parse_metadata(body: &[&str], line_number: usize) -> Result<Metadata, Error> {
+    let mut metadata = Metadata::new();
+
+    for (offset, line) in body.iter().enumerate() {
+        let error = |message: String| Error::BeancountParseError(line_number + offset + 1, message);
+        let tokens = tokenize(line).map_err(error)?;
+
+        if tokens.is_empty() {
+            continue;
+        }
+        let (key, value) =
+            metadata_entry(&tokens).ok_or_else(|| error("expected metadata".to_string()))?;
+        metadata.insert(key, value);
+    }
+
+    Ok(metadata)
+}
+
+// The key and value of a `key: value` metadata line, or `None` for other lines.
+fn metadata_entry(tokens: &[Token]) -> Option<(String, String)> {
+    let key = match tokens.first() {
+        Some(Token::Word(first))
+            if first.ends_with(':') && first.starts_with(|c: char| c.is_ascii_lowercase()) =>
+        {
+            first.trim_end_matches(':').to_string()
+        }
+        _ => return None,
+    };
+    let value = match tokens.get(1) {
+        Some(Token::Str(value) | Token::Word(value)) => value.clone(),
+        None => String::new(),
+    };
+
+    Some((key, value))
+}
+
+// The values of a custom directive: strings, accounts, amounts, numbers, booleans and dates.
+fn parse_custom_values(args: &[Token]) -> Result<Vec<CustomValue>, String> {
+    let mut values: Vec<CustomValue> = Vec::new();
+    let mut tokens = args.iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        let word = match token {
+            Token::Str(value) => {
+                values.push(CustomValue::String(value.clone()));
+                continue;
+            }
+            Token::Word(word) => word.as_str(),
+        };
+
+        let value = match word {
+            "TRUE" => CustomValue::Bool(true),
+            "FALSE" => CustomValue::Bool(false),
+            word if word.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                CustomValue::Account(Account::from_str(word)?)
+            }
+            word if word.len() == 10 && matches!(word.as_bytes()[4], b'-' | b'/') => {
+                CustomValue::Date(parse_date(word)?)
+            }
+            word => {
+                let number = parse_number(word)?;
+                match tokens.peek() {
+                    Some(Token::Word(currency))
+                        if currency.starts_with(|c: char| c.is_ascii_uppercase()) =>
+                    {
+                        tokens.next();
+                        CustomValue::Amount(number, currency.clone())
+                    }
+                    _ => CustomValue::Number(number),
+                }
+            }
+        };
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+// the string at `index` of a directive's arguments
+fn string(args: &[Token], index: usize) -> Result<String, String> {
+    match args.get(index) {
+        Some(Token::Str(string)) => Ok(string.clone()),
+        _ => Err(format!("missing string argument {}", index + 1)),
+    }
+}
+
+// the word at `index` of a directive's arguments
+fn word(args: &[Token], index: usize) -> Result<&str, String> {
+    match args.get(index) {
+        Some(Token::Word(word)) => Ok(word),
+        _ => Err(format!("missing argument {}", index + 1)),
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y/%m/%d"))
+        .map_err(|_| format!("expected a date or keyword, found \"{}\"", date))
+}
+
+fn parse_number(number: &str) -> Result<Decimal, String> {
+    Decimal::from_str_exact(&number.replace(',', ""))
+        .map_err(|_| format!("invalid number \"{}\"", number))
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    fn account(account_type: AccountType, account: &str, sub_account: Option<&str>) -> Account {
+        Account {
+            account_type,
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            account: account.to_string(),
+            sub_account: sub_account.map(|s| s.to_string()),
+            name: None,
+        }
+    }
+
+    fn render(directives: &[Directive]) -> String {
+        directives.iter().map(|d| d.to_formatted_string()).collect()
+    }
+
+    #[test]
+    fn should_round_trip_formatted_directives() {
+        // Arrange
+        let equity = Account {
+            account_type: AccountType::Equity,
+            country: "GBP".to_string(),
+            institution: String::new(),
+            account: "OpeningBalances".to_string(),
+            sub_account: None,
+            name: None,
+        };
+        let personal = account(AccountType::Assets, "Personal", None);
+        let holiday = account(AccountType::Assets, "Personal", Some("Holiday"));
+        let groceries = account(AccountType::Expenses, "Groceries", None);
+        let directives = vec![
+            Directive::Option("title".to_string(), "Monzo Accounts".to_string()),
+            Directive::Include("include/amex.beancount".to_string()),
+            Directive::Comment("Asset Accounts".to_string()),
+            Directive::Open(date(1), equity.clone(), vec![], None),
+            Directive::Open(date(1), personal.clone(), vec![], None),
+            Directive::Open(
+                date(1),
+                holiday.clone(),
+                vec![],
+                Some("Pot Holiday".to_string()),
+            ),
+            Directive::Open(date(1), groceries.clone(), vec!["USD".to_string()], None),
+            Directive::Transaction(Box::new(Transaction {
+                date: date(2),
+                flag: Flag::Incomplete,
+                comment: Some("TESCO STORES ".to_string()),
+                payee: Some("Tesco".to_string()),
+                narration: "Weekly \"big\" shop \\ essentials".to_string(),
+                tags: BTreeSet::from(["food".to_string()]),
+                links: BTreeSet::from(["receipt-42".to_string()]),
+                metadata: Metadata::from([
+                    ("monzo-id".to_string(), "tx_0001".to_string()),
+                    ("description".to_string(), "TESCO \"EXTRA\"".to_string()),
+                ]),
+                postings: vec![
+                    Posting {
+                        account: groceries.clone(),
+                        amount: Some(Decimal::new(1234, 2)),
+                        currency: "GBP".to_string(),
+                        price: Some(TotalPrice {
+                            amount: Decimal::new(1560, 2),
+                            currency: "USD".to_string(),
+                        }),
+                        metadata: Metadata::from([(
+                            "category".to_string(),
+                            "Groceries".to_string(),
+                        )]),
+                    },
+                    Posting {
+                        account: personal.clone(),
+                        amount: Some(Decimal::new(-1234, 2)),
+                        currency: "GBP".to_string(),
+                        price: None,
+                        metadata: Metadata::new(),
+                    },
+                ],
+            })),
+            Directive::Balance(
+                date(3),
+                personal.clone(),
+                Decimal::new(98766, 2),
+                "GBP".to_string(),
+            ),
+            Directive::Price(
+                date(3),
+                "GBP".to_string(),
+                Decimal::new(127, 2),
+                "USD".to_string(),
+                Metadata::from([("source".to_string(), "ECB".to_string())]),
+            ),
+            Directive::Commodity(
+                date(3),
+                "USD".to_string(),
+                Metadata::from([("name".to_string(), "US Dollar".to_string())]),
+            ),
+            Directive::Pad(date(4), holiday.clone(), equity, Metadata::new()),
+            Directive::Note(
+                date(5),
+                holiday.clone(),
+                "Saving for \"Paris\"".to_string(),
+                Metadata::new(),
+            ),
+            Directive::Document(
+                date(5),
+                personal,
+                "documents/2024-06-05 receipt.pdf".to_string(),
+                Metadata::from([("monzo-id".to_string(), "tx_0001".to_string())]),
+            ),
+            Directive::Event(
+                date(6),
+                "location".to_string(),
+                "Paris, France".to_string(),
+                Metadata::new(),
+            ),
+            Directive::Query(
+                date(6),
+                "groceries".to_string(),
+                "SELECT date, narration WHERE account ~ 'Groceries'".to_string(),
+                Metadata::new(),
+            ),
+            Directive::Custom(
+                date(7),
+                "budget".to_string(),
+                vec![
+                    CustomValue::Account(groceries),
+                    CustomValue::String("monthly".to_string()),
+                    CustomValue::Amount(Decimal::new(25000, 2), "GBP".to_string()),
+                    CustomValue::Number(Decimal::new(3, 0)),
+                    CustomValue::Bool(false),
+                    CustomValue::Date(date(30)),
+                ],
+                Metadata::new(),
+            ),
+            Directive::Close(date(30), holiday, Some("Pot Holiday deleted".to_string())),
+        ];
+        let formatted = render(&directives);
+
+        // Act
+        let parsed = parse(&formatted).unwrap();
+
+        // Assert
+        assert_eq!(parsed.len(), directives.len());
+        assert_eq!(render(&parsed), formatted);
+    }
+
+    #[test]
+    fn should_parse_full_transaction_syntax() {
+        // Arrange
+        let input = r#"
+plugin "beancount.plugins.auto_accounts"
+
+2024-06-02 ! "Tesco" "Weekly \"big\" shop" #food ^receipt-42
+  monzo-id: "tx_0001"
+  ; a comment inside the transaction
+  Expenses:GBP:Monzo:Groceries        12.34 GBP @@ 15.60 USD
+    category: "Groceries"
+  Assets:GBP:Monzo:Personal
+"#;
+
+        // Act
+        let directives = parse(input).unwrap();
+
+        // Assert
+        assert_eq!(directives.len(), 1);
+        let transaction = match &directives[0] {
+            Directive::Transaction(transaction) => transaction,
+            other => panic!("Expected a transaction, got {:?}", other),
+        };
+        assert_eq!(transaction.date, date(2));
+        assert_eq!(transaction.flag, Flag::Incomplete);
+        assert_eq!(transaction.payee, Some("Tesco".to_string()));
+        assert_eq!(transaction.narration, "Weekly \"big\" shop");
+        assert_eq!(transaction.tags, BTreeSet::from(["food".to_string()]));
+        assert_eq!(
+            transaction.links,
+            BTreeSet::from(["receipt-42".to_string()])
+        );
+        assert_eq!(transaction.postings.len(), 2);
+        assert_eq!(transaction.postings[0].amount, Some(Decimal::new(1234, 2)));
+        assert_eq!(transaction.postings[0].currency, "GBP");
+        assert_eq!(
+            transaction.postings[0].price,
+            Some(TotalPrice {
+                amount: Decimal::new(1560, 2),
+                currency: "USD".to_string(),
+            })
+        );
+        assert_eq!(transaction.postings[1].amount, None);
+        assert_eq!(transaction.metadata["monzo-id"], "tx_0001");
+        assert_eq!(transaction.postings[0].metadata["category"], "Groceries");
+        assert!(transaction.postings[1].metadata.is_empty());
+        assert_eq!(
+            transaction.postings[1].account.to_string(),
+            "Assets:GBP:Monzo:Personal"
+        );
+    }
+
+    #[test]
+    fn should_keep_per_unit_price_as_total() {
+        let input = "2024-06-02 * \"Diner\"\n  Expenses:GBP:Monzo:Travel  -2.00 USD @ 0.80 GBP\n  Assets:GBP:Monzo:Personal\n";
+
+        let directives = parse(input).unwrap();
+
+        let Directive::Transaction(transaction) = &directives[0] else {
+            panic!("Expected a transaction, got {:?}", directives[0]);
+        };
+        assert_eq!(
+            transaction.postings[0].price,
+            Some(TotalPrice {
+                amount: Decimal::new(1600, 3),
+                currency: "GBP".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_report_line_of_error() {
+        let input = "option \"title\" \"Accounts\"\n\n2024-06-02 * \"Tesco\"\n  Expenses:GBP:Monzo:groceries  12.34 GBP\n  Assets:GBP:Monzo:Personal\n";
+
+        let result = parse(input);
+
+        match result {
+            Err(Error::BeancountParseError(line, message)) => {
+                assert_eq!(line, 4);
+                assert!(
+                    message.contains("Expenses:GBP:Monzo:groceries"),
+                    "{}",
+                    message
+                );
+            }
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_reject_unsupported_directives() {
+        let result = parse("2024-06-02 budget Assets:GBP:Monzo:Personal 250.00 GBP\n");
+
+        assert!(matches!(result, Err(Error::BeancountParseError(1, _))));
+    }
+}
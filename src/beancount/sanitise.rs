@@ -0,0 +1,101 @@
+//! Makes names from bank data safe to use in Beancount accounts.
+//!
+//! Each component of an account name after its type must match the Beancount grammar
+//! `[A-Z0-9][A-Za-z0-9-]*`. Names from sheets and exports are free text, so they are first
+//! rewritten by the user's transliteration rules, then folded to ASCII (`Café` -> `Cafe`,
+//! `🍔` -> `Hamburger`), Pascal cased and stripped of anything the grammar doesn't allow.
+//!
+//! Narration, payees and metadata keep the raw text and are escaped when quoted instead.
+
+use std::collections::BTreeMap;
+
+use convert_case::{Case, Casing};
+use deunicode::deunicode;
+
+/// The component used for a name with nothing left once made safe.
+const EMPTY_COMPONENT: &str = "Unknown";
+
+/// User rules replacing text in account names, e.g. `"&": " And "`, applied in key order before
+/// the built-in transliteration.
+pub type Transliterations = BTreeMap<String, String>;
+
+/// Apply the user's transliteration `rules` to `value`.
+pub fn transliterate(value: &str, rules: &Transliterations) -> String {
+    rules.iter().fold(value.to_string(), |value, (from, to)| {
+        value.replace(from, to)
+    })
+}
+
+/// The Beancount account component for `value`, e.g. `"eating out"` -> `EatingOut`.
+pub fn account_component(value: &str) -> String {
+    // anything other than a letter or digit separates words
+    let words: String = deunicode(value)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect();
+    let component = words.to_case(Case::Pascal);
+
+    if component.is_empty() {
+        EMPTY_COMPONENT.to_string()
+    } else {
+        component
+    }
+}
+
+/// Whether `component` is a valid Beancount account component.
+pub fn is_account_component(component: &str) -> bool {
+    let mut chars = component.chars();
+
+    matches!(chars.next(), Some(c) if c.is_ascii_uppercase() || c.is_ascii_digit())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_make_valid_account_components() {
+        let cases = [
+            ("eating out", "EatingOut"),
+            ("Café Nero", "CafeNero"),
+            ("Food & Drink", "FoodDrink"),
+            ("Bills/Utilities", "BillsUtilities"),
+            ("2024 trip", "2024Trip"),
+            ("🍔", "Hamburger"),
+            ("!!!", "Unknown"),
+        ];
+
+        for (name, expected) in cases {
+            let component = account_component(name);
+
+            assert_eq!(component, expected, "for {:?}", name);
+            assert!(is_account_component(&component), "for {:?}", name);
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_account_components() {
+        assert!(is_account_component("Groceries"));
+        assert!(is_account_component("7-Eleven"));
+        assert!(!is_account_component("groceries"));
+        assert!(!is_account_component("Eating Out"));
+        assert!(!is_account_component("Café"));
+        assert!(!is_account_component(""));
+    }
+
+    #[test]
+    fn should_apply_transliterations_in_order() {
+        let rules = Transliterations::from([
+            ("&".to_string(), " And ".to_string()),
+            ("ß".to_string(), "ss".to_string()),
+        ]);
+
+        let transliterated = transliterate("Straße & Co", &rules);
+
+        assert_eq!(transliterated, "Strasse  And  Co");
+        assert_eq!(account_component(&transliterated), "StrasseAndCo");
+    }
+}
@@ -0,0 +1,289 @@
+//! Defines the interface between providers of bank transactions and Beancount generation.
+//!
+//! A [`TransactionSource`] supplies the accounts it knows about and a normalised list of
+//! transactions for each. Generation consumes sources through this trait only, so new
+//! providers can be added without changing the classifier or directive pipeline.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError as Error;
+
+use super::google::transactions::Transaction;
+
+/// The payment type of a transfer between an account and one of its pots.
+pub const POT_TRANSFER: &str = "Pot transfer";
+
+/// Categories that are classified as income or transfers rather than expenses.
+const NON_EXPENSE_CATEGORIES: [&str; 3] = ["Income", "Savings", "Transfers"];
+
+/// An account whose transactions are supplied by a source.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+pub struct SourceAccount {
+    pub country: String,
+    pub institution: String,
+    pub name: String,
+}
+
+/// A pot holding money set aside within an account.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Pot {
+    pub id: String,
+    pub name: String,
+    pub currency: String,
+    pub opened: NaiveDate,
+    /// The date the pot was deleted, if it has been.
+    pub closed: Option<NaiveDate>,
+}
+
+/// A sheet or export row that couldn't be parsed into a transaction, or a transaction that
+/// couldn't be posted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidRow {
+    pub sheet: String,
+    /// The row number as shown in the sheet, where the header is row 1, or `None` for a
+    /// transaction that was parsed but couldn't be posted.
+    pub row: Option<usize>,
+    pub column: String,
+    pub message: String,
+    /// The raw cell values of the row.
+    pub values: Vec<String>,
+}
+
+impl InvalidRow {
+    /// The row's parse or posting failure as an error.
+    pub fn error(&self) -> Error {
+        match self.row {
+            Some(row) => Error::RowParseError(
+                self.sheet.clone(),
+                row,
+                self.column.clone(),
+                self.message.clone(),
+            ),
+            None => Error::TransactionPostingError(self.sheet.clone(), self.message.clone()),
+        }
+    }
+}
+
+/// A provider of normalised transactions.
+pub trait TransactionSource {
+    /// A human readable label for the source, used in messages.
+    fn label(&self) -> String;
+
+    /// The accounts this source has transactions for.
+    fn accounts(&self) -> Vec<SourceAccount>;
+
+    /// The transactions for `account`.
+    fn transactions(&self, account: &SourceAccount) -> &[Transaction];
+
+    /// The rows that were left out because they couldn't be parsed.
+    fn invalid_rows(&self) -> &[InvalidRow] {
+        &[]
+    }
+
+    /// The sorted, unique expense categories used by the transactions for `account`.
+    fn categories(&self, account: &SourceAccount) -> Vec<String> {
+        let filtered_set: HashSet<String> = self
+            .transactions(account)
+            .iter()
+            .filter(|t| !NON_EXPENSE_CATEGORIES.contains(&t.category.as_str()))
+            .flat_map(|t| {
+                let splits = t.category_split.iter().flatten();
+                std::iter::once(t.category.clone()).chain(splits.map(|s| s.category.clone()))
+            })
+            .collect();
+
+        let mut categories: Vec<String> = filtered_set.into_iter().collect();
+        categories.sort();
+
+        categories
+    }
+
+    /// The pots belonging to `account`.
+    ///
+    /// By default pots are inferred from the account's transactions, opening on the date
+    /// of their first transaction. Sources that know when pots were created and deleted
+    /// should override this.
+    fn pots(&self, account: &SourceAccount) -> Vec<Pot> {
+        let mut pots: BTreeMap<String, Pot> = BTreeMap::new();
+
+        for tx in self.transactions(account) {
+            let name = match &tx.pot {
+                Some(name) => name,
+                None => continue,
+            };
+            let pot = pots.entry(name.clone()).or_insert_with(|| Pot {
+                id: name.clone(),
+                name: name.clone(),
+                currency: tx.currency.clone(),
+                opened: tx.date,
+                closed: None,
+            });
+            pot.opened = pot.opened.min(tx.date);
+        }
+
+        pots.into_values().collect()
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::beancount::google::transactions::CategorySplit;
+    use crate::beancount::money::Decimal;
+
+    use super::*;
+
+    struct FixtureSource {
+        account: SourceAccount,
+        transactions: Vec<Transaction>,
+    }
+
+    impl TransactionSource for FixtureSource {
+        fn label(&self) -> String {
+            "fixture".to_string()
+        }
+
+        fn accounts(&self) -> Vec<SourceAccount> {
+            vec![self.account.clone()]
+        }
+
+        fn transactions(&self, account: &SourceAccount) -> &[Transaction] {
+            if account == &self.account {
+                &self.transactions
+            } else {
+                &[]
+            }
+        }
+    }
+
+    fn transaction(category: &str) -> Transaction {
+        Transaction {
+            category: category.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn categories_are_sorted_unique_expenses() {
+        // Arrange
+        let account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let source = FixtureSource {
+            account: account.clone(),
+            transactions: vec![
+                transaction("Groceries"),
+                transaction("Income"),
+                transaction("Bills"),
+                transaction("Transfers"),
+                transaction("Groceries"),
+                transaction("Savings"),
+            ],
+        };
+
+        // Act
+        let categories = source.categories(&account);
+
+        // Assert
+        assert_eq!(
+            categories,
+            vec!["Bills".to_string(), "Groceries".to_string()]
+        );
+    }
+
+    #[test]
+    fn categories_include_split_categories() {
+        // Arrange
+        let account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let split = |category: &str| CategorySplit {
+            category: category.to_string(),
+            amount: Decimal::new(-100, 2),
+        };
+        let source = FixtureSource {
+            account: account.clone(),
+            transactions: vec![Transaction {
+                category_split: Some(vec![split("Groceries"), split("Household")]),
+                ..transaction("Groceries")
+            }],
+        };
+
+        // Act
+        let categories = source.categories(&account);
+
+        // Assert
+        assert_eq!(
+            categories,
+            vec!["Groceries".to_string(), "Household".to_string()]
+        );
+    }
+
+    #[test]
+    fn pots_are_inferred_from_transactions() {
+        // Arrange
+        let account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let pot_transfer = |day: u32, pot: &str| Transaction {
+            date: NaiveDate::from_ymd_opt(2024, 6, day).unwrap(),
+            payment_type: POT_TRANSFER.to_string(),
+            currency: "GBP".to_string(),
+            pot: Some(pot.to_string()),
+            ..Default::default()
+        };
+        let source = FixtureSource {
+            account: account.clone(),
+            transactions: vec![
+                pot_transfer(5, "Holiday"),
+                transaction("Groceries"),
+                pot_transfer(2, "Holiday"),
+                pot_transfer(9, "Car Tax"),
+            ],
+        };
+
+        // Act
+        let pots = source.pots(&account);
+
+        // Assert
+        assert_eq!(pots.len(), 2);
+        assert_eq!(pots[0].name, "Car Tax");
+        assert_eq!(pots[1].name, "Holiday");
+        assert_eq!(pots[1].opened, NaiveDate::from_ymd_opt(2024, 6, 2).unwrap());
+        assert_eq!(pots[1].closed, None);
+    }
+
+    #[test]
+    fn categories_for_unknown_account_are_empty() {
+        // Arrange
+        let account = SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        };
+        let other = SourceAccount {
+            name: "Business".to_string(),
+            ..account.clone()
+        };
+        let source = FixtureSource {
+            account,
+            transactions: vec![transaction("Groceries")],
+        };
+
+        // Act
+        let categories = source.categories(&other);
+
+        // Assert
+        assert!(categories.is_empty());
+    }
+}
@@ -0,0 +1,396 @@
+//! Persists fetched transactions to a local SQLite database.
+//!
+//! The store is the system of record for generation: transactions fetched from each source
+//! are saved keyed by their Monzo transaction id, and the Beancount file is rendered from the
+//! store. Transactions are kept when they disappear from a source, and `generate --offline`
+//! renders from the store without fetching anything.
+
+use std::{collections::HashMap, path::Path};
+
+use chrono::NaiveDate;
+use rusqlite::{params, types::Type, Connection};
+
+use crate::error::AppError as Error;
+
+use super::google::transactions::{CategorySplit, Transaction};
+use super::money::{from_minor, to_minor};
+use super::source::{Pot, SourceAccount, TransactionSource};
+
+const SCHEMA: &str = r#"
+CREATE TABLE IF NOT EXISTS transactions (
+    id             TEXT PRIMARY KEY,
+    country        TEXT NOT NULL,
+    institution    TEXT NOT NULL,
+    account        TEXT NOT NULL,
+    date           TEXT NOT NULL,
+    payment_type   TEXT NOT NULL,
+    name           TEXT NOT NULL,
+    category       TEXT NOT NULL,
+    amount         INTEGER NOT NULL,
+    currency       TEXT NOT NULL,
+    local_amount   INTEGER NOT NULL,
+    local_currency TEXT NOT NULL,
+    notes          TEXT,
+    description    TEXT,
+    category_split TEXT,
+    pot            TEXT,
+    sheet          TEXT
+);
+
+CREATE TABLE IF NOT EXISTS pots (
+    id          TEXT NOT NULL,
+    country     TEXT NOT NULL,
+    institution TEXT NOT NULL,
+    account     TEXT NOT NULL,
+    name        TEXT NOT NULL,
+    currency    TEXT NOT NULL,
+    opened      TEXT NOT NULL,
+    closed      TEXT,
+    PRIMARY KEY (country, institution, account, id)
+);
+"#;
+
+/// A SQLite database of transactions and pots.
+pub struct TransactionStore {
+    conn: Connection,
+}
+
+/// The contents of a `TransactionStore`, loaded as a transaction source.
+#[derive(Debug, Default)]
+pub struct StoredTransactions {
+    pub accounts: Vec<SourceAccount>,
+    pub transactions: HashMap<SourceAccount, Vec<Transaction>>,
+    pub pots: HashMap<SourceAccount, Vec<Pot>>,
+}
+
+impl TransactionStore {
+    /// Open the store at `path`, creating it if it doesn't exist.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(TransactionStore { conn })
+    }
+
+    /// Insert or update the transactions and pots of every account in `source`.
+    pub fn save(&mut self, source: &dyn TransactionSource) -> Result<(), Error> {
+        let db = self.conn.transaction()?;
+
+        for account in source.accounts() {
+            for tx in source.transactions(&account) {
+                let category_split = tx
+                    .category_split
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?;
+
+                db.execute(
+                    "INSERT INTO transactions (id, country, institution, account, date, payment_type,
+                         name, category, amount, currency, local_amount, local_currency, notes,
+                         description, category_split, pot, sheet)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                         ?17)
+                     ON CONFLICT(id) DO UPDATE SET
+                         country = excluded.country, institution = excluded.institution,
+                         account = excluded.account, date = excluded.date,
+                         payment_type = excluded.payment_type, name = excluded.name,
+                         category = excluded.category, amount = excluded.amount,
+                         currency = excluded.currency, local_amount = excluded.local_amount,
+                         local_currency = excluded.local_currency, notes = excluded.notes,
+                         description = excluded.description,
+                         category_split = excluded.category_split, pot = excluded.pot,
+                         sheet = excluded.sheet",
+                    params![
+                        tx.id,
+                        account.country,
+                        account.institution,
+                        account.name,
+                        tx.date,
+                        tx.payment_type,
+                        tx.name,
+                        tx.category,
+                        to_minor(tx.amount, &tx.currency)?,
+                        tx.currency,
+                        to_minor(tx.local_amount, &tx.local_currency)?,
+                        tx.local_currency,
+                        tx.notes,
+                        tx.description,
+                        category_split,
+                        tx.pot,
+                        tx.sheet,
+                    ],
+                )?;
+            }
+
+            for pot in source.pots(&account) {
+                db.execute(
+                    "INSERT INTO pots (id, country, institution, account, name, currency, opened, closed)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                     ON CONFLICT(country, institution, account, id) DO UPDATE SET
+                         name = excluded.name, currency = excluded.currency,
+                         opened = MIN(opened, excluded.opened), closed = excluded.closed",
+                    params![
+                        pot.id,
+                        account.country,
+                        account.institution,
+                        account.name,
+                        pot.name,
+                        pot.currency,
+                        pot.opened,
+                        pot.closed,
+                    ],
+                )?;
+            }
+        }
+
+        db.commit()?;
+
+        Ok(())
+    }
+
+    /// Load every stored account, transaction and pot.
+    pub fn load(&self) -> Result<StoredTransactions, Error> {
+        let mut stored = StoredTransactions::default();
+
+        let mut statement = self.conn.prepare(
+            "SELECT country, institution, account, id, date, payment_type, name, category, amount,
+                    currency, local_amount, local_currency, notes, description, category_split, pot,
+                    sheet
+             FROM transactions ORDER BY date, rowid",
+        )?;
+        let rows = statement.query_map([], |row| {
+            let account = SourceAccount {
+                country: row.get(0)?,
+                institution: row.get(1)?,
+                name: row.get(2)?,
+            };
+            let category_split = row
+                .get::<_, Option<String>>(14)?
+                .map(|s| serde_json::from_str::<Vec<CategorySplit>>(&s))
+                .transpose()
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(14, Type::Text, e.into()))?;
+            let currency: String = row.get(9)?;
+            let local_currency: String = row.get(11)?;
+            let tx = Transaction {
+                id: row.get(3)?,
+                date: row.get::<_, NaiveDate>(4)?,
+                payment_type: row.get(5)?,
+                name: row.get(6)?,
+                category: row.get(7)?,
+                amount: from_minor(row.get(8)?, &currency),
+                currency,
+                local_amount: from_minor(row.get(10)?, &local_currency),
+                local_currency,
+                notes: row.get(12)?,
+                description: row.get(13)?,
+                category_split,
+                pot: row.get(15)?,
+                sheet: row.get(16)?,
+            };
+            Ok((account, tx))
+        })?;
+
+        for row in rows {
+            let (account, tx) = row?;
+            stored.add_account(&account);
+            stored.transactions.entry(account).or_default().push(tx);
+        }
+
+        let mut statement = self.conn.prepare(
+            "SELECT country, institution, account, id, name, currency, opened, closed
+             FROM pots ORDER BY name",
+        )?;
+        let rows = statement.query_map([], |row| {
+            let account = SourceAccount {
+                country: row.get(0)?,
+                institution: row.get(1)?,
+                name: row.get(2)?,
+            };
+            let pot = Pot {
+                id: row.get(3)?,
+                name: row.get(4)?,
+                currency: row.get(5)?,
+                opened: row.get(6)?,
+                closed: row.get(7)?,
+            };
+            Ok((account, pot))
+        })?;
+
+        for row in rows {
+            let (account, pot) = row?;
+            stored.add_account(&account);
+            stored.pots.entry(account).or_default().push(pot);
+        }
+
+        Ok(stored)
+    }
+}
+
+impl StoredTransactions {
+    fn add_account(&mut self, account: &SourceAccount) {
+        if !self.accounts.contains(account) {
+            self.accounts.push(account.clone());
+        }
+    }
+}
+
+impl TransactionSource for StoredTransactions {
+    fn label(&self) -> String {
+        "transaction store".to_string()
+    }
+
+    fn accounts(&self) -> Vec<SourceAccount> {
+        self.accounts.clone()
+    }
+
+    fn transactions(&self, account: &SourceAccount) -> &[Transaction] {
+        self.transactions
+            .get(account)
+            .map(|t| t.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn pots(&self, account: &SourceAccount) -> Vec<Pot> {
+        self.pots.get(account).cloned().unwrap_or_default()
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    fn test_account() -> SourceAccount {
+        SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "Personal".to_string(),
+        }
+    }
+
+    fn test_transaction(id: &str, day: u32, amount: i64) -> Transaction {
+        Transaction {
+            id: id.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 6, day).unwrap(),
+            payment_type: "Card payment".to_string(),
+            name: "Tesco".to_string(),
+            category: "Groceries".to_string(),
+            amount: from_minor(amount, "GBP"),
+            currency: "GBP".to_string(),
+            local_amount: from_minor(amount, "GBP"),
+            local_currency: "GBP".to_string(),
+            notes: None,
+            description: Some("TESCO STORES".to_string()),
+            category_split: Some(vec![CategorySplit {
+                category: "Groceries".to_string(),
+                amount: from_minor(amount, "GBP"),
+            }]),
+            pot: None,
+            sheet: Some("Personal Account Transactions".to_string()),
+        }
+    }
+
+    fn stored(transactions: Vec<Transaction>, pots: Vec<Pot>) -> StoredTransactions {
+        StoredTransactions {
+            accounts: vec![test_account()],
+            transactions: HashMap::from([(test_account(), transactions)]),
+            pots: HashMap::from([(test_account(), pots)]),
+        }
+    }
+
+    #[test]
+    fn should_round_trip_transactions_and_pots() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let mut store = TransactionStore::open(&dir.child("transactions.sqlite")).unwrap();
+        let pot = Pot {
+            id: "pot_0001".to_string(),
+            name: "Holiday".to_string(),
+            currency: "GBP".to_string(),
+            opened: NaiveDate::from_ymd_opt(2024, 1, 10).unwrap(),
+            closed: NaiveDate::from_ymd_opt(2024, 5, 20),
+        };
+        let source = stored(
+            vec![
+                test_transaction("tx_0002", 3, -350),
+                test_transaction("tx_0001", 2, -1234),
+            ],
+            vec![pot.clone()],
+        );
+
+        // Act
+        store.save(&source).unwrap();
+        let loaded = store.load().unwrap();
+
+        // Assert
+        assert_eq!(loaded.accounts(), vec![test_account()]);
+        let transactions = loaded.transactions(&test_account());
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].id, "tx_0001");
+        assert_eq!(transactions[0].amount, from_minor(-1234, "GBP"));
+        assert_eq!(
+            transactions[0].description,
+            Some("TESCO STORES".to_string())
+        );
+        assert_eq!(
+            transactions[0].category_split.as_ref().unwrap()[0].amount,
+            from_minor(-1234, "GBP")
+        );
+        assert_eq!(
+            transactions[0].sheet,
+            Some("Personal Account Transactions".to_string())
+        );
+        assert_eq!(loaded.pots(&test_account()), vec![pot]);
+    }
+
+    #[test]
+    fn should_report_unreadable_category_split() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let mut store = TransactionStore::open(&dir.child("transactions.sqlite")).unwrap();
+        store
+            .save(&stored(vec![test_transaction("tx_0001", 2, -1234)], vec![]))
+            .unwrap();
+        store
+            .conn
+            .execute("UPDATE transactions SET category_split = 'Groceries'", [])
+            .unwrap();
+
+        // Act
+        let result = store.load();
+
+        // Assert
+        assert!(matches!(result, Err(Error::DatabaseError(_))));
+    }
+
+    #[test]
+    fn should_keep_transactions_missing_from_later_fetches() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let mut store = TransactionStore::open(&dir.child("transactions.sqlite")).unwrap();
+        store
+            .save(&stored(
+                vec![
+                    test_transaction("tx_0001", 2, -1234),
+                    test_transaction("tx_0002", 3, -350),
+                ],
+                vec![],
+            ))
+            .unwrap();
+
+        // Act
+        store
+            .save(&stored(vec![test_transaction("tx_0002", 3, -400)], vec![]))
+            .unwrap();
+        let loaded = store.load().unwrap();
+
+        // Assert
+        let transactions = loaded.transactions(&test_account());
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0].id, "tx_0001");
+        assert_eq!(transactions[1].amount, from_minor(-400, "GBP"));
+    }
+}
@@ -1,37 +1,75 @@
 //! Represents a Beancount transaction and its formatting.
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
 use chrono::NaiveDate;
 
 use super::account::{Account, AccountType};
+use super::money::{exponent, Decimal};
+
+/// Beancount metadata, written as `key: "value"` lines under a transaction or posting.
+pub type Metadata = BTreeMap<String, String>;
 
 /// Represents a Beancount transaction.
 #[derive(Debug)]
 pub struct Transaction {
     pub date: NaiveDate,
+    pub flag: Flag,
     pub comment: Option<String>,
-    pub notes: String,
-    pub postings: Postings,
+    /// Who the transaction was with, shown in Fava's payee view.
+    pub payee: Option<String>,
+    /// What the transaction was for.
+    pub narration: String,
+    /// Tags, without the leading `#`, e.g. `trip-paris`.
+    pub tags: BTreeSet<String>,
+    /// Links, without the leading `^`, tying related transactions together.
+    pub links: BTreeSet<String>,
+    pub metadata: Metadata,
+    /// The legs of the transaction, written in order. At most one may leave out its amount.
+    pub postings: Vec<Posting>,
 }
 
-/// Represents a Beancount double entry posting.
-#[derive(Debug, Clone)]
-pub struct Postings {
-    pub to: Posting,
-    pub from: Posting,
+/// The flag of a transaction, shown before its payee.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Flag {
+    /// `*`, a transaction that is known to be right.
+    #[default]
+    Complete,
+    /// `!`, a transaction that needs reviewing, listed separately by Fava.
+    Incomplete,
 }
 
-/// represents a Beancount Liability posting.
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Flag::Complete => write!(f, "*"),
+            Flag::Incomplete => write!(f, "!"),
+        }
+    }
+}
+
+/// Represents a Beancount posting.
 #[derive(Debug, Clone)]
 pub struct Posting {
     pub account: Account,
-    pub amount: f64,
+    /// The amount, or `None` for Beancount to balance the transaction.
+    pub amount: Option<Decimal>,
+    pub currency: String,
+    /// The `@@` total price of the amount in another currency, e.g. for spending abroad.
+    pub price: Option<TotalPrice>,
+    pub metadata: Metadata,
+}
+
+/// The total price of a posting's amount, always positive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TotalPrice {
+    pub amount: Decimal,
     pub currency: String,
-    pub description: Option<String>,
 }
 
 impl Transaction {
     #[must_use]
-
     pub fn to_formatted_string(&self) -> String {
         let comment = match &self.comment {
             Some(s) if s.trim().is_empty() => String::new(),
@@ -39,25 +77,92 @@ impl Transaction {
             None => String::new(),
         };
 
+        let postings: String = self
+            .postings
+            .iter()
+            .map(|posting| {
+                format!(
+                    "  {}\n{}",
+                    posting.to_formatted_string(),
+                    format_metadata(&posting.metadata, "    ")
+                )
+            })
+            .collect();
+
+        let payee = match &self.payee {
+            Some(payee) => format!("{} ", quote(payee)),
+            None => String::new(),
+        };
+
+        let tags_and_links: String = self
+            .tags
+            .iter()
+            .map(|tag| format!(" #{}", tag))
+            .chain(self.links.iter().map(|link| format!(" ^{}", link)))
+            .collect();
+
         format!(
-            "{}{} * \"{}\"\n  {}\n  {}\n",
+            "{}{} {} {}{}{}\n{}{}",
             comment,
             self.date,
-            self.notes,
-            self.postings.to.to_formatted_string(),
-            self.postings.from.to_formatted_string(),
+            self.flag,
+            payee,
+            quote(&self.narration),
+            tags_and_links,
+            format_metadata(&self.metadata, "  "),
+            postings,
         )
     }
+
+    /// Check the transaction has at least two postings and at most one elided amount.
+    pub fn check_postings(&self) -> Result<(), String> {
+        if self.postings.len() < 2 {
+            return Err(format!(
+                "transactions need at least two postings, found {}",
+                self.postings.len()
+            ));
+        }
+
+        let elided = self.postings.iter().filter(|p| p.amount.is_none()).count();
+        if elided > 1 {
+            return Err("only one posting can leave out its amount".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Write each metadata entry on its own line, indented by `indent`.
+pub(crate) fn format_metadata(metadata: &Metadata, indent: &str) -> String {
+    metadata
+        .iter()
+        .map(|(key, value)| format!("{}{}: {}\n", indent, key, quote(value)))
+        .collect()
+}
+
+/// A Beancount string literal, escaping backslashes and double quotes.
+pub(crate) fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
 impl Posting {
     fn to_formatted_string(&self) -> String {
-        let amount = self.amount / 100.0;
+        let Some(amount) = self.amount else {
+            return self.account.to_string();
+        };
+        let precision = exponent(&self.currency) as usize;
+        let price = match &self.price {
+            Some(price) => {
+                let precision = exponent(&price.currency) as usize;
+                format!(" @@ {:.precision$} {}", price.amount, price.currency)
+            }
+            None => String::new(),
+        };
 
-        match self.account.account_type {
+        let posting = match self.account.account_type {
             AccountType::Assets => {
                 format!(
-                    "{:<50} {:>10.2} {}",
+                    "{:<50} {:>10.precision$} {}",
                     self.account.to_string(),
                     amount,
                     self.currency,
@@ -65,7 +170,7 @@ impl Posting {
             }
             AccountType::Liabilities => {
                 format!(
-                    "{:<50} {:>10.2} {}",
+                    "{:<50} {:>10.precision$} {}",
                     self.account.to_string(),
                     amount,
                     self.currency,
@@ -73,7 +178,7 @@ impl Posting {
             }
             AccountType::Income => {
                 format!(
-                    "{:<50} {:>10.2} {}",
+                    "{:<50} {:>10.precision$} {}",
                     self.account.to_string(),
                     amount,
                     self.currency,
@@ -81,7 +186,7 @@ impl Posting {
             }
             AccountType::Expenses => {
                 format!(
-                    "{:<50} {:>10.2} {}",
+                    "{:<50} {:>10.precision$} {}",
                     self.account.to_string(),
                     amount,
                     self.currency,
@@ -89,13 +194,15 @@ impl Posting {
             }
             AccountType::Equity => {
                 format!(
-                    "{:<50} {:>10.2} {}",
+                    "{:<50} {:>10.precision$} {}",
                     self.account.to_string(),
                     amount,
                     self.currency,
                 )
             }
-        }
+        };
+
+        posting + &price
     }
 }
 
@@ -118,7 +225,7 @@ mod tests {
             institution: "Monzo".to_string(),
             account: "Groceries".to_string(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         };
 
         let asset_account = Account {
@@ -127,36 +234,45 @@ mod tests {
             institution: "Monzo".to_string(),
             account: "Personal".to_string(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         };
 
         let liability_posting = Posting {
             account: liability_account,
-            amount: -1000.0,
+            amount: Some(Decimal::new(-1000, 2)),
             currency: "GBP".to_string(),
-            description: Some("AMEX PAYMENT ACH PAYMENT".to_string()),
+            price: None,
+            metadata: Metadata::from([(
+                "description".to_string(),
+                "AMEX \"ACH\" PAYMENT".to_string(),
+            )]),
         };
 
         let asset_posting = Posting {
             account: asset_account,
-            amount: 1000.0,
+            amount: Some(Decimal::new(1000, 2)),
             currency: "GBP".to_string(),
-            description: None,
+            price: None,
+            metadata: Metadata::new(),
         };
 
-        let postings = Postings {
-            from: asset_posting,
-            to: liability_posting,
-        };
+        let postings = vec![liability_posting, asset_posting];
         let transaction = Transaction {
             comment: Some("ONLINE PAYMENT - THANK YOU".to_string()),
             date,
-            notes: "Yacht purchase".to_string(),
+            flag: Flag::Complete,
+            payee: Some("AMEX".to_string()),
+            narration: "Yacht purchase".to_string(),
+            tags: BTreeSet::from(["boat".to_string(), "2024-refit".to_string()]),
+            links: BTreeSet::from(["amex-2024-06".to_string()]),
+            metadata: Metadata::from([("monzo-id".to_string(), "tx_0001".to_string())]),
             postings,
         };
         let expected = r#"; ONLINE PAYMENT - THANK YOU
-2024-06-13 * "Yacht purchase"
+2024-06-13 * "AMEX" "Yacht purchase" #2024-refit #boat ^amex-2024-06
+  monzo-id: "tx_0001"
   Liabilities:GBP:Monzo:Groceries                        -10.00 GBP
+    description: "AMEX \"ACH\" PAYMENT"
   Assets:GBP:Monzo:Personal                               10.00 GBP
 "#;
 
@@ -167,4 +283,187 @@ mod tests {
         println!("{}", transaction_string);
         assert_eq!(transaction_string, expected);
     }
+
+    #[test]
+    fn split_transaction_formatted() {
+        // Arrange
+        let account = |account_type: AccountType, sub_account: &str| Account {
+            account_type,
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            account: "Personal".to_string(),
+            sub_account: Some(sub_account.to_string()).filter(|s| !s.is_empty()),
+            name: None,
+        };
+        let posting = |account: Account, amount: Option<Decimal>| Posting {
+            account,
+            amount,
+            currency: "GBP".to_string(),
+            price: None,
+            metadata: Metadata::new(),
+        };
+        let transaction = Transaction {
+            comment: None,
+            date: NaiveDate::from_ymd_opt(2024, 6, 13).unwrap(),
+            flag: Flag::Complete,
+            payee: None,
+            narration: "Tesco".to_string(),
+            tags: BTreeSet::new(),
+            links: BTreeSet::new(),
+            metadata: Metadata::new(),
+            postings: vec![
+                posting(
+                    account(AccountType::Expenses, "Groceries"),
+                    Some(Decimal::new(800, 2)),
+                ),
+                posting(
+                    account(AccountType::Expenses, "Household"),
+                    Some(Decimal::new(200, 2)),
+                ),
+                posting(account(AccountType::Assets, ""), None),
+            ],
+        };
+        let expected = r#"2024-06-13 * "Tesco"
+  Expenses:GBP:Monzo:Personal:Groceries                    8.00 GBP
+  Expenses:GBP:Monzo:Personal:Household                    2.00 GBP
+  Assets:GBP:Monzo:Personal
+"#;
+
+        // Act
+        let transaction_string = transaction.to_formatted_string();
+
+        // Assert
+        assert_eq!(transaction_string, expected);
+        assert!(transaction.check_postings().is_ok());
+    }
+
+    #[test]
+    fn posting_uses_currency_decimal_places() {
+        let posting = |amount: Decimal, currency: &str| Posting {
+            account: Account {
+                account_type: AccountType::Expenses,
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                account: "Personal".to_string(),
+                sub_account: Some("Travel".to_string()),
+                name: None,
+            },
+            amount: Some(amount),
+            currency: currency.to_string(),
+            price: None,
+            metadata: Metadata::new(),
+        };
+
+        assert_eq!(
+            posting(Decimal::new(1500, 0), "JPY").to_formatted_string(),
+            "Expenses:GBP:Monzo:Personal:Travel                       1500 JPY"
+        );
+        assert_eq!(
+            posting(Decimal::new(1234, 3), "KWD").to_formatted_string(),
+            "Expenses:GBP:Monzo:Personal:Travel                      1.234 KWD"
+        );
+    }
+
+    #[test]
+    fn posting_with_total_price_formatted() {
+        let posting = Posting {
+            account: Account {
+                account_type: AccountType::Expenses,
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                account: "Personal".to_string(),
+                sub_account: Some("Travel".to_string()),
+                name: None,
+            },
+            amount: Some(Decimal::new(1500, 0)),
+            currency: "JPY".to_string(),
+            price: Some(TotalPrice {
+                amount: Decimal::new(802, 2),
+                currency: "GBP".to_string(),
+            }),
+            metadata: Metadata::new(),
+        };
+
+        assert_eq!(
+            posting.to_formatted_string(),
+            "Expenses:GBP:Monzo:Personal:Travel                       1500 JPY @@ 8.02 GBP"
+        );
+    }
+
+    #[test]
+    fn payee_and_narration_escaped() {
+        let transaction = Transaction {
+            comment: None,
+            date: NaiveDate::from_ymd_opt(2024, 6, 13).unwrap(),
+            flag: Flag::Complete,
+            payee: Some("Joe's \"Cafe\"".to_string()),
+            narration: "Lunch \\ coffee".to_string(),
+            tags: BTreeSet::new(),
+            links: BTreeSet::new(),
+            metadata: Metadata::new(),
+            postings: vec![],
+        };
+
+        assert_eq!(
+            transaction.to_formatted_string(),
+            "2024-06-13 * \"Joe's \\\"Cafe\\\"\" \"Lunch \\\\ coffee\"\n"
+        );
+    }
+
+    #[test]
+    fn flagged_transaction_formatted() {
+        let transaction = Transaction {
+            comment: None,
+            date: NaiveDate::from_ymd_opt(2024, 6, 13).unwrap(),
+            flag: Flag::Incomplete,
+            payee: None,
+            narration: "Transfer".to_string(),
+            tags: BTreeSet::new(),
+            links: BTreeSet::new(),
+            metadata: Metadata::from([("review".to_string(), "Unknown account".to_string())]),
+            postings: vec![],
+        };
+
+        assert_eq!(
+            transaction.to_formatted_string(),
+            "2024-06-13 ! \"Transfer\"\n  review: \"Unknown account\"\n"
+        );
+    }
+
+    #[test]
+    fn should_allow_one_elided_amount() {
+        let posting = Posting {
+            account: Account {
+                account_type: AccountType::Assets,
+                country: "GBP".to_string(),
+                institution: "Monzo".to_string(),
+                account: "Personal".to_string(),
+                sub_account: None,
+                name: None,
+            },
+            amount: None,
+            currency: String::new(),
+            price: None,
+            metadata: Metadata::new(),
+        };
+        let mut transaction = Transaction {
+            comment: None,
+            date: NaiveDate::from_ymd_opt(2024, 6, 13).unwrap(),
+            flag: Flag::Complete,
+            payee: None,
+            narration: "Tesco".to_string(),
+            tags: BTreeSet::new(),
+            links: BTreeSet::new(),
+            metadata: Metadata::new(),
+            postings: vec![posting.clone()],
+        };
+
+        assert!(transaction.check_postings().is_err());
+
+        transaction.postings.push(posting);
+        assert_eq!(
+            transaction.check_postings(),
+            Err("only one posting can leave out its amount".to_string())
+        );
+    }
 }
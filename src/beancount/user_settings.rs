@@ -7,19 +7,138 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::AppError as Error;
 
-use super::{account::Account, google::GoogleSheetAccount};
+use super::{
+    account::{Account, AccountType, TEMPLATE_PLACEHOLDERS},
+    google::GoogleSheetAccount,
+    monzo::MonzoApiSettings,
+    monzo_csv::MonzoCsvAccount,
+    sanitise::Transliterations,
+};
 
 /// A struct representing a user settings file on disk
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserSettings {
     pub start_date: NaiveDate,
+    /// The maximum number of sheets to download at the same time.
+    pub fetch_concurrency: Option<usize>,
     pub googlesheet_accounts: Option<Vec<GoogleSheetAccount>>,
+    pub monzo_csv_accounts: Option<Vec<MonzoCsvAccount>>,
+    pub monzo_api: Option<MonzoApiSettings>,
+    /// What to do with sheet and export rows that can't be parsed.
+    #[serde(default)]
+    pub invalid_rows: InvalidRowPolicy,
+    /// Which posting of a transaction made abroad carries the `@@` total price.
+    #[serde(default)]
+    pub price_annotation: PriceAnnotation,
+    /// Replacements made in names before they are used in accounts, e.g. `"&": " And "`.
+    #[serde(default)]
+    pub transliterations: Transliterations,
+    /// Templates naming the accounts of each type, instead of the default hierarchy.
+    #[serde(default)]
+    pub account_names: AccountNames,
     pub assets: Option<Vec<Account>>,
     pub liabilities: Option<Vec<Account>>,
     pub income: Option<Vec<Account>>,
     pub expenses: Option<Vec<Account>>,
 }
 
+/// The handling of rows that can't be parsed into transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvalidRowPolicy {
+    /// Leave the row out and report it after generation.
+    #[default]
+    Skip,
+    /// Leave the row out, report it and write it to the quarantine file.
+    Quarantine,
+    /// Stop generation without saving or writing anything.
+    Fail,
+}
+
+/// The posting that carries the total price of a transaction made in a foreign currency.
+///
+/// Either way the expense is recorded in the local currency, e.g. `1.00 USD`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceAnnotation {
+    /// The expense is priced in the account currency: `1.00 USD @@ 0.80 GBP`.
+    #[default]
+    Local,
+    /// The account posting is priced in the local currency: `-0.80 GBP @@ 1.00 USD`.
+    Account,
+}
+
+/// A template naming the accounts of each type, e.g. `Expenses:{category}`, where the default
+/// is `Type:{country}:{institution}:{account}:{sub_account}`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountNames {
+    pub assets: Option<String>,
+    pub liabilities: Option<String>,
+    pub income: Option<String>,
+    pub expenses: Option<String>,
+    pub equity: Option<String>,
+}
+
+impl AccountNames {
+    /// The template for accounts of `account_type`, if one is configured.
+    pub fn template(&self, account_type: &AccountType) -> Option<&str> {
+        match account_type {
+            AccountType::Assets => self.assets.as_deref(),
+            AccountType::Liabilities => self.liabilities.as_deref(),
+            AccountType::Income => self.income.as_deref(),
+            AccountType::Expenses => self.expenses.as_deref(),
+            AccountType::Equity => self.equity.as_deref(),
+        }
+    }
+
+    // Each template must name accounts of its own type, using only the known placeholders. Asset
+    // and liability templates must keep the sub-account, so that pots aren't named as the account
+    // they belong to.
+    fn check(&self) -> Result<(), Error> {
+        let types = [
+            AccountType::Assets,
+            AccountType::Liabilities,
+            AccountType::Income,
+            AccountType::Expenses,
+            AccountType::Equity,
+        ];
+
+        for account_type in types {
+            let Some(template) = self.template(&account_type) else {
+                continue;
+            };
+            let error =
+                |message: String| Err(Error::AccountTemplateError(template.to_string(), message));
+
+            let prefix = format!("{}:", account_type);
+            if !template.starts_with(&prefix) {
+                return error(format!("must start with \"{}\"", prefix));
+            }
+
+            let mut rest = template;
+            while let Some(start) = rest.find('{') {
+                let end = rest[start..]
+                    .find('}')
+                    .map_or(rest.len(), |end| start + end + 1);
+                let placeholder = &rest[start..end];
+                if !TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+                    return error(format!("unknown placeholder \"{}\"", placeholder));
+                }
+                rest = &rest[end..];
+            }
+
+            let keeps_sub_account = template.contains("{sub_account}");
+            if matches!(account_type, AccountType::Assets | AccountType::Liabilities)
+                && !keeps_sub_account
+            {
+                return error("must include \"{sub_account}\" to name pots".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl UserSettings {
     /// Constructs a new instance of `UserSettings` from a configuration file.
     pub fn from_config(config_file_path: PathBuf) -> Result<Self, Error> {
@@ -33,10 +152,66 @@ impl UserSettings {
         let user_settings = match cfg.try_deserialize::<UserSettings>() {
             Ok(settings) => settings,
             Err(e) => {
-                return Err(Error::ConfigurationError(e));
+                return Err(e.into());
             }
         };
+        user_settings.account_names.check()?;
 
         Ok(user_settings)
     }
+
+    /// Whether `account` is one of those listed under `assets`, `liabilities`, `income` or
+    /// `expenses`.
+    pub fn is_configured(&self, account: &Account) -> bool {
+        [
+            &self.assets,
+            &self.liabilities,
+            &self.income,
+            &self.expenses,
+        ]
+        .into_iter()
+        .flatten()
+        .any(|accounts| accounts.contains(account))
+    }
+}
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account_names(yaml: &str) -> AccountNames {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn should_accept_templates_with_known_placeholders() {
+        let names = account_names(
+            "expenses: \"Expenses:{category}\"\n\
+             assets: \"Assets:{institution}:{account}:{sub_account}\"\n",
+        );
+
+        assert!(names.check().is_ok());
+    }
+
+    #[test]
+    fn should_reject_invalid_templates() {
+        let templates = [
+            "expenses: \"Assets:{category}\"",
+            "expenses: \"Expenses:{categories}\"",
+            "expenses: \"Expenses:{category\"",
+            "assets: \"Assets:{institution}:{account}\"",
+        ];
+
+        for template in templates {
+            let result = account_names(template).check();
+
+            assert!(
+                matches!(result, Err(Error::AccountTemplateError(..))),
+                "for {:?}",
+                template
+            );
+        }
+    }
 }
@@ -2,8 +2,12 @@
 
 use crate::{beancount::Beancount, error::AppError as Error};
 
-pub async fn generate(beancount: &Beancount) -> Result<(), Error> {
-    beancount.generate().await?;
+pub async fn generate(beancount: &Beancount, offline: bool) -> Result<(), Error> {
+    if offline {
+        beancount.generate_offline().await?;
+    } else {
+        beancount.generate().await?;
+    }
 
     Ok(())
 }
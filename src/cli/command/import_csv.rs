@@ -9,30 +9,49 @@
 //! ```
 //! - **date**: the date of the transaction
 //! - **description**: a description of the transaction
-//! - **amount**: the amount of the transaction in the account's currency
+//! - **amount**: the amount of the transaction in the account's currency, e.g. `GBP`
 //! - **local_currency**: the currency of the transaction
 //! - **local_amount**: the amount of the transaction in the local currency
 //! - **category**: the category of the transaction
+//! - **type** (optional): the Monzo payment type of the transaction
+//! - **balance** (optional): the pot's balance after the transaction
+//!
+//! Each file holds the transactions of one pot, named by the file, e.g. `holiday.csv`. A file
+//! in a directory named after a configured account, e.g. `personal/holiday.csv`, belongs to
+//! that account, and a file directly in the `import` directory belongs to the only configured
+//! account. Files for Monzo API accounts are skipped, as the API's pot transactions are
+//! generated already.
+//!
+//! Pot deposits and withdrawals are generated from the account's pot transfers, so records
+//! with a `type` of `Pot transfer` are skipped, as are records whose description starts with
+//! `Deposit` or `Withdrawal` in files without a `type` column. The pot's account is opened and
+//! closed by `generate`.
+//!
+//! The last balance given on each day is asserted at the start of the following day.
 //!
 
+use std::collections::BTreeSet;
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use chrono::NaiveDate;
-use config::Case;
-use convert_case::Casing;
 use csv::Reader;
 use serde::Deserialize;
 
 use crate::beancount::datafile_paths::DataFilePaths;
-use crate::beancount::transaction::Postings;
+use crate::beancount::generate::balance_directives::day_after;
+use crate::beancount::generate::{name_accounts, pot_account};
+use crate::beancount::money::{exponent, Decimal};
+use crate::beancount::sanitise::account_component;
+use crate::beancount::source::{SourceAccount, POT_TRANSFER};
+use crate::beancount::user_settings::UserSettings;
 
 use crate::{
     beancount::{
         account::{Account, AccountType},
         directive::Directive,
-        transaction::{Posting, Transaction as BeancountTransaction},
+        transaction::{Flag, Metadata, Posting, Transaction as BeancountTransaction},
         Beancount,
     },
     error::AppError as Error,
@@ -43,18 +62,34 @@ use crate::{
 struct Record {
     date: NaiveDate,
     description: String,
-    amount: f64,
+    amount: Decimal,
     local_currency: Option<String>,
-    local_amount: Option<f64>,
+    local_amount: Option<Decimal>,
     category: Option<String>,
+    #[serde(default, rename = "type")]
+    payment_type: Option<String>,
+    #[serde(default)]
+    balance: Option<Decimal>,
 }
 
 /// Imports the CSV files from the `import` directory and generates Beancount files.
 pub async fn import(beancount: &Beancount) -> Result<(), Error> {
-    let csv_files = get_csv_files(&beancount.data_file_paths.import_dir)?;
+    let import_dir = &beancount.data_file_paths.import_dir;
+    let accounts = configured_accounts(&beancount.user_settings);
+    let csv_files = get_csv_files(import_dir)?;
 
     for csv_file in csv_files {
-        let directives = process_csv_file(&csv_file)?;
+        let account = pot_owner(&csv_file, import_dir, &accounts)?;
+        if is_api_account(&account, &beancount.user_settings) {
+            println!(
+                "Skipping {}: pot transactions are generated from the Monzo API.",
+                csv_file.display()
+            );
+            continue;
+        }
+
+        let mut directives = process_csv_file(&csv_file, &account)?;
+        name_accounts(&mut directives, &beancount.user_settings)?;
         let mut beancount_file = beanacount_file(&csv_file, &beancount.data_file_paths)?;
         write_directives(&mut beancount_file, directives)?;
     }
@@ -62,7 +97,7 @@ pub async fn import(beancount: &Beancount) -> Result<(), Error> {
     Ok(())
 }
 
-// get the .CSV files in `dir`
+// get the .CSV files in `dir` and its account directories
 fn get_csv_files(dir: &PathBuf) -> Result<Vec<PathBuf>, Error> {
     let mut csv_files = Vec::new();
 
@@ -74,8 +109,14 @@ fn get_csv_files(dir: &PathBuf) -> Result<Vec<PathBuf>, Error> {
         let entry = entry?;
         let path = entry.path();
 
-        // Check if the path is a file and has a .csv extension
-        if path.is_file() && path.extension().and_then(std::ffi::OsStr::to_str) == Some("csv") {
+        if path.is_dir() {
+            for entry in fs::read_dir(&path)? {
+                let path = entry?.path();
+                if is_csv_file(&path) {
+                    csv_files.push(path);
+                }
+            }
+        } else if is_csv_file(&path) {
             csv_files.push(path);
         }
     }
@@ -83,38 +124,103 @@ fn get_csv_files(dir: &PathBuf) -> Result<Vec<PathBuf>, Error> {
     Ok(csv_files)
 }
 
-/// Read a CSV file of transactions and create a Beancount file
-pub(crate) fn process_csv_file(csv_file: &PathBuf) -> Result<Vec<Directive>, Error> {
+fn is_csv_file(path: &Path) -> bool {
+    path.is_file() && path.extension().and_then(std::ffi::OsStr::to_str) == Some("csv")
+}
+
+// The accounts read from sheets, exports and the Monzo API, which pot files can belong to.
+fn configured_accounts(user_settings: &UserSettings) -> Vec<SourceAccount> {
+    let sheets = user_settings.googlesheet_accounts.iter().flatten();
+    let exports = user_settings.monzo_csv_accounts.iter().flatten();
+    let api = user_settings.monzo_api.iter().flat_map(|api| &api.accounts);
+
+    let mut accounts: Vec<SourceAccount> = Vec::new();
+    for account in sheets
+        .map(SourceAccount::from)
+        .chain(exports.map(SourceAccount::from))
+        .chain(api.map(SourceAccount::from))
+    {
+        if !accounts.contains(&account) {
+            accounts.push(account);
+        }
+    }
+
+    accounts
+}
+
+fn is_api_account(account: &SourceAccount, user_settings: &UserSettings) -> bool {
+    user_settings
+        .monzo_api
+        .iter()
+        .flat_map(|api| &api.accounts)
+        .any(|api_account| SourceAccount::from(api_account) == *account)
+}
+
+// The account holding the pot in `csv_file`, named by the file's directory within `import_dir`.
+fn pot_owner(
+    csv_file: &Path,
+    import_dir: &Path,
+    accounts: &[SourceAccount],
+) -> Result<SourceAccount, Error> {
+    let owners: Vec<&SourceAccount> = match csv_file.parent() {
+        Some(dir) if dir != import_dir => {
+            let dir_name = dir.file_name().unwrap_or_default().to_string_lossy();
+            accounts
+                .iter()
+                .filter(|account| account_component(&account.name) == account_component(&dir_name))
+                .collect()
+        }
+        _ => accounts.iter().collect(),
+    };
+
+    match owners.as_slice() {
+        [account] => Ok((*account).clone()),
+        _ => Err(Error::ApplicationError(format!(
+            "can't tell which account the pot in {} belongs to, place it in a directory named \
+             after one of the configured accounts",
+            csv_file.display()
+        ))),
+    }
+}
+
+/// Read a CSV file of the transactions of one of `account`'s pots and create its directives
+pub(crate) fn process_csv_file(
+    csv_file: &PathBuf,
+    account: &SourceAccount,
+) -> Result<Vec<Directive>, Error> {
     let records = get_sorted_records(csv_file)?;
-    let account_name = account_name_from_csv_file(csv_file);
+    let pot_name = pot_name_from_csv_file(csv_file)?;
     let mut directives: Vec<Directive> = vec![];
 
-    directives.push(Directive::Comment(account_name.to_string()));
+    directives.push(Directive::Comment(pot_name.clone()));
     directives.push(Directive::Comment("Transactions".to_string()));
-    directives.extend(generate_directives(records.clone(), &account_name)?);
-    directives.push(close_account(&records, &account_name));
+    directives.extend(generate_directives(records.clone(), account, &pot_name)?);
+
+    let balances = balance_directives(&records, account, &pot_name);
+    if !balances.is_empty() {
+        directives.push(Directive::Comment("Balances".to_string()));
+        directives.extend(balances);
+    }
 
     Ok(directives)
 }
 
-// e.g. essential-variable-pot.csv -> EssentialVariablePot
-fn account_name_from_csv_file(csv_file: &Path) -> String {
-    let account_name = csv_file
+// e.g. essential-variable-pot.csv -> essential-variable-pot, the pot `Essential Variable Pot`
+fn pot_name_from_csv_file(csv_file: &Path) -> Result<String, Error> {
+    csv_file
         .file_stem()
-        .unwrap()
-        .to_string_lossy()
-        .to_case(Case::Pascal);
-
-    account_name
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| Error::InvalidFileName(csv_file.display().to_string()))
 }
 
-// e.g./a/b/include/essential-variable-pot.csv -> /a/b/accounts/essential-variable-pot.beanfile
+// e.g./a/b/import/personal/holiday.csv -> /a/b/include/personal-holiday.beancount
 fn beanacount_file(csv_file: &Path, file_paths: &DataFilePaths) -> Result<File, Error> {
-    // Create a new path by iterating over the components of the original path
+    // Name the file by its path within the import directory
     let csv_file_name = csv_file
-        .file_name()
-        .and_then(|file_name| file_name.to_str()) // Convert OsStr to &str
-        .map(|file_name_str| file_name_str.to_string())
+        .strip_prefix(&file_paths.import_dir)
+        .ok()
+        .and_then(|file_name| file_name.to_str()) // Convert Path to &str
+        .map(|file_name_str| file_name_str.replace(std::path::MAIN_SEPARATOR, "-"))
         .ok_or_else(|| Error::InvalidFileName(csv_file.display().to_string()))?;
 
     // Change the file name extension
@@ -127,7 +233,8 @@ fn beanacount_file(csv_file: &Path, file_paths: &DataFilePaths) -> Result<File,
 
 // deserialise the records from the CSV file
 fn get_sorted_records(csv_file_path: &PathBuf) -> Result<Vec<Record>, Error> {
-    let mut reader = Reader::from_path(csv_file_path).expect("Failed to open CSV file");
+    let mut reader = Reader::from_path(csv_file_path)
+        .map_err(|e| Error::ApplicationError(format!("{}: {}", csv_file_path.display(), e)))?;
     let mut records: Vec<Record> = reader
         .deserialize()
         .filter_map(|result| result.ok())
@@ -138,19 +245,24 @@ fn get_sorted_records(csv_file_path: &PathBuf) -> Result<Vec<Record>, Error> {
     Ok(records)
 }
 
-fn generate_directives(records: Vec<Record>, pot_name: &str) -> Result<Vec<Directive>, Error> {
+fn generate_directives(
+    records: Vec<Record>,
+    account: &SourceAccount,
+    pot_name: &str,
+) -> Result<Vec<Directive>, Error> {
     let mut directives: Vec<Directive> = vec![];
 
     for record in &records {
-        let to_posting = prepare_to_posting(record, pot_name)?;
-        let from_posting = prepare_from_posting(record, pot_name)?;
+        // Deposits and withdrawals are generated from the account's pot transfers.
+        if is_transfer(record) {
+            continue;
+        }
 
-        let postings = Postings {
-            to: to_posting,
-            from: from_posting,
-        };
+        let to_posting = prepare_to_posting(record, account, pot_name)?;
+        let from_posting = prepare_from_posting(record, account, pot_name)?;
 
-        let transaction = prepare_transaction(&postings, record);
+        let transaction =
+            prepare_transaction(vec![to_posting, from_posting], record, &account.country);
 
         directives.push(Directive::Transaction(Box::new(transaction)));
     }
@@ -158,150 +270,159 @@ fn generate_directives(records: Vec<Record>, pot_name: &str) -> Result<Vec<Direc
     Ok(directives)
 }
 
-fn prepare_to_posting(record: &Record, pot_name: &str) -> Result<Posting, Error> {
-    let account = if is_income(&record.category.clone().unwrap_or("".to_string()))
-        || is_transfer(&record.description)
-    {
-        Account {
-            account_type: AccountType::Assets,
-            country: "GBP".to_string(),
-            institution: "Monzo".to_string(),
-            account: pot_name.to_string(),
-            sub_account: None,
-            transaction_id: None,
+// The closing balance of each day that has one, asserted on the following day.
+fn balance_directives(
+    records: &[Record],
+    account: &SourceAccount,
+    pot_name: &str,
+) -> Vec<Directive> {
+    let mut directives: Vec<Directive> = vec![];
+
+    for (i, record) in records.iter().enumerate() {
+        let Some(balance) = record.balance else {
+            continue;
+        };
+
+        // a later record on the same day carries the day's closing balance
+        let later_balance = records[i + 1..]
+            .iter()
+            .take_while(|r| r.date == record.date)
+            .any(|r| r.balance.is_some());
+        if later_balance {
+            continue;
         }
+
+        directives.push(Directive::Balance(
+            day_after(record.date),
+            pot_account(account, pot_name),
+            balance,
+            account.country.clone(),
+        ));
+    }
+
+    directives
+}
+
+fn prepare_to_posting(
+    record: &Record,
+    source_account: &SourceAccount,
+    pot_name: &str,
+) -> Result<Posting, Error> {
+    let account = if is_income(&record.category.clone().unwrap_or("".to_string())) {
+        pot_account(source_account, pot_name)
     } else {
         Account {
             account_type: AccountType::Expenses,
-            country: "GBP".to_string(),
-            institution: "Monzo".to_string(),
-            account: "Personal".to_string(),
+            country: source_account.country.clone(),
+            institution: source_account.institution.clone(),
+            account: source_account.name.clone(),
             sub_account: record.category.clone(),
-            transaction_id: None,
+            name: None,
         }
     };
 
-    let amount = if is_transfer(&record.description)
-        || is_income(&record.category.clone().unwrap_or("".to_string()))
-    {
-        record.amount * 100.0
+    let amount = if is_income(&record.category.clone().unwrap_or("".to_string())) {
+        record.amount
     } else {
-        -record.amount * 100.0
+        -record.amount
     };
 
-    let currency = "GBP".to_string();
-    let description = Some(record.description.clone());
+    // the amounts are in the account's currency
+    let currency = source_account.country.clone();
 
     Ok(Posting {
         account,
-        amount,
+        amount: Some(amount),
         currency,
-        description,
+        price: None,
+        metadata: Metadata::new(),
     })
 }
 
-fn prepare_from_posting(record: &Record, pot_name: &str) -> Result<Posting, Error> {
-    // let category = record.category.clone().unwrap_or("".to_string());
+fn prepare_from_posting(
+    record: &Record,
+    source_account: &SourceAccount,
+    pot_name: &str,
+) -> Result<Posting, Error> {
     let account = if is_income(&record.category.clone().unwrap_or("".to_string())) {
         Account {
             account_type: AccountType::Income,
-            country: "GBP".to_string(),
-            institution: "Monzo".to_string(),
-            account: pot_name.to_string(),
-            sub_account: None,
-            transaction_id: None,
-        }
-    } else if is_transfer(&record.description) {
-        Account {
-            account_type: AccountType::Assets,
-            country: "GBP".to_string(),
-            institution: "Monzo".to_string(),
-            account: "Personal".to_string(),
+            country: source_account.country.clone(),
+            institution: source_account.institution.clone(),
+            account: source_account.name.clone(),
             sub_account: None,
-            transaction_id: None,
+            name: None,
         }
     } else {
-        Account {
-            account_type: AccountType::Assets,
-            country: "GBP".to_string(),
-            institution: "Monzo".to_string(),
-            account: pot_name.to_string(),
-            sub_account: None,
-            transaction_id: None,
-        }
+        pot_account(source_account, pot_name)
     };
 
-    let amount = if is_transfer(&record.description)
-        || is_income(&record.category.clone().unwrap_or("".to_string()))
-    {
-        record.amount * -100.0
+    let amount = if is_income(&record.category.clone().unwrap_or("".to_string())) {
+        -record.amount
     } else {
-        record.amount * 100.0
+        record.amount
     };
 
-    let currency = "GBP".to_string();
-    let description = Some(record.description.clone());
+    // the amounts are in the account's currency
+    let currency = source_account.country.clone();
 
     Ok(Posting {
         account,
-        amount,
+        amount: Some(amount),
         currency,
-        description,
+        price: None,
+        metadata: Metadata::new(),
     })
 }
 
-fn is_transfer(description: &str) -> bool {
-    description.starts_with("Withdrawal") || description.starts_with("Deposit")
+// A deposit to or withdrawal from the pot, named by its description in files without a type.
+fn is_transfer(record: &Record) -> bool {
+    match record.payment_type.as_deref() {
+        Some(payment_type) => payment_type == POT_TRANSFER,
+        None => {
+            record.description.starts_with("Withdrawal")
+                || record.description.starts_with("Deposit")
+        }
+    }
 }
 
 fn is_income(category: &str) -> bool {
     category == "Income"
 }
 
-fn close_account(records: &[Record], pot_name: &str) -> Directive {
-    let last_record = records.last().unwrap();
-
-    // Close the account
-    let account = Account {
-        account_type: AccountType::Assets,
-        country: "GBP".to_string(),
-        institution: "Monzo".to_string(),
-        account: pot_name.to_string(),
-        sub_account: None,
-        transaction_id: None,
-    };
-
-    Directive::Close(
-        last_record.date,
-        account,
-        Some(format!("Close {}", pot_name.to_case(Case::Title))),
-    )
-}
-
-fn prepare_transaction(postings: &Postings, tx: &Record) -> BeancountTransaction {
-    let date = tx.date;
-    let mut notes = tx.description.clone();
-
-    // Convert local_amount to string or initialize to empty string if None.
-    let mut comment = tx.local_amount.map_or(String::new(), |a| a.to_string());
-
-    // Append local_amount and local_currency to notes if both are Some.
+// The transaction for `tx`, with the description as its payee, as `generate` takes the payee from
+// the bank's name for the transaction. The amount spent abroad is kept as `local-amount`.
+fn prepare_transaction(
+    postings: Vec<Posting>,
+    tx: &Record,
+    currency: &str,
+) -> BeancountTransaction {
+    let mut metadata = Metadata::from([("description".to_string(), tx.description.clone())]);
+    if let Some(payment_type) = &tx.payment_type {
+        metadata.insert("payment-type".to_string(), payment_type.clone());
+    }
     if let (Some(local_amount), Some(local_currency)) =
         (tx.local_amount, tx.local_currency.as_ref())
     {
-        notes += &format!(" {} {}", local_amount, local_currency);
-    }
-
-    // Remove everything from "Amount" onwards in comment if found.
-    if let Some(index) = comment.find("Amount") {
-        comment.truncate(index);
+        if local_currency != currency {
+            let precision = exponent(local_currency) as usize;
+            metadata.insert(
+                "local-amount".to_string(),
+                format!("{:.precision$} {}", local_amount, local_currency),
+            );
+        }
     }
 
     BeancountTransaction {
-        comment: Some(comment),
-        date,
-        notes,
-        postings: postings.clone(),
+        comment: None,
+        date: tx.date,
+        flag: Flag::Complete,
+        payee: None,
+        narration: tx.description.clone(),
+        tags: BTreeSet::new(),
+        links: BTreeSet::new(),
+        metadata,
+        postings,
     }
 }
 
@@ -312,3 +433,180 @@ fn write_directives(beancount_file: &mut File, directives: Vec<Directive>) -> Re
 
     Ok(())
 }
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use super::*;
+
+    fn personal() -> SourceAccount {
+        SourceAccount {
+            country: "GBP".to_string(),
+            institution: "Monzo".to_string(),
+            name: "personal".to_string(),
+        }
+    }
+
+    #[test]
+    fn should_assert_closing_balance_of_each_day() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let csv_file = dir.child("holiday-pot.csv");
+        fs::write(
+            &csv_file,
+            "date,description,amount,local_currency,local_amount,category,balance\n\
+             2024-06-01,Cafe,-3.5,GBP,-3.5,Eating out,96.5\n\
+             2024-06-01,Museum,-10,GBP,-10,Entertainment,86.5\n\
+             2024-06-02,Train,-4,GBP,-4,Transport,\n",
+        )
+        .unwrap();
+
+        // Act
+        let directives = process_csv_file(&csv_file, &personal()).unwrap();
+
+        // Assert
+        let balances: Vec<String> = directives
+            .iter()
+            .filter(|d| matches!(d, Directive::Balance(..)))
+            .map(|d| d.to_formatted_string())
+            .collect();
+        assert_eq!(
+            balances,
+            vec!["2024-06-02 balance Assets:GBP:Monzo:Personal:HolidayPot                    86.50 GBP\n"]
+        );
+    }
+
+    #[test]
+    fn should_post_to_pot_account_opened_by_generate() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let csv_file = dir.child("holiday-pot.csv");
+        fs::write(
+            &csv_file,
+            "date,description,amount,local_currency,local_amount,category\n\
+             2024-06-01,Deposit from Personal,50,GBP,50,Transfers\n\
+             2024-06-02,Cafe,-3.5,GBP,-3.5,Eating out\n\
+             2024-06-03,Withdrawal to Personal,-10,GBP,-10,Transfers\n",
+        )
+        .unwrap();
+        let opened = pot_account(&personal(), "Holiday Pot");
+
+        // Act
+        let directives = process_csv_file(&csv_file, &personal()).unwrap();
+
+        // Assert
+        let transactions: Vec<&BeancountTransaction> = directives
+            .iter()
+            .filter_map(|d| match d {
+                Directive::Transaction(tx) => Some(tx.as_ref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(
+            transactions[0].postings[1].account.to_string(),
+            opened.to_string()
+        );
+    }
+
+    #[test]
+    fn should_find_pot_owner_by_directory() {
+        let import_dir = Path::new("/data/import");
+        let joint = SourceAccount {
+            name: "Joint".to_string(),
+            ..personal()
+        };
+        let accounts = vec![personal(), joint.clone()];
+
+        let owner = pot_owner(
+            &import_dir.join("joint").join("holiday.csv"),
+            import_dir,
+            &accounts,
+        );
+
+        assert_eq!(owner.unwrap(), joint);
+        assert!(pot_owner(&import_dir.join("holiday.csv"), import_dir, &accounts).is_err());
+        assert_eq!(
+            pot_owner(&import_dir.join("holiday.csv"), import_dir, &accounts[..1]).unwrap(),
+            personal()
+        );
+    }
+
+    #[test]
+    fn should_use_account_currency() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let csv_file = dir.child("travel.csv");
+        fs::write(
+            &csv_file,
+            "date,description,amount,local_currency,local_amount,category,balance\n\
+             2024-06-01,Cafe,-3.5,EUR,-3.5,Eating out,96.5\n",
+        )
+        .unwrap();
+        let account = SourceAccount {
+            country: "EUR".to_string(),
+            institution: "Bunq".to_string(),
+            name: "Everyday".to_string(),
+        };
+
+        // Act
+        let directives = process_csv_file(&csv_file, &account).unwrap();
+
+        // Assert
+        let currencies: Vec<&str> = directives
+            .iter()
+            .flat_map(|d| match d {
+                Directive::Transaction(tx) => {
+                    tx.postings.iter().map(|p| p.currency.as_str()).collect()
+                }
+                Directive::Balance(_, _, _, currency) => vec![currency.as_str()],
+                _ => vec![],
+            })
+            .collect();
+        assert_eq!(currencies, vec!["EUR", "EUR", "EUR"]);
+    }
+
+    #[test]
+    fn should_take_narration_from_description() {
+        // Arrange
+        let dir = TempDir::new().unwrap();
+        let csv_file = dir.child("holiday.csv");
+        fs::write(
+            &csv_file,
+            "date,description,amount,local_currency,local_amount,category\n\
+             2024-06-02,PATH TAPP PAYGO,-0.8,USD,-1.0,Transport\n",
+        )
+        .unwrap();
+
+        // Act
+        let directives = process_csv_file(&csv_file, &personal()).unwrap();
+
+        // Assert
+        let rendered: String = directives
+            .iter()
+            .filter(|d| matches!(d, Directive::Transaction(_)))
+            .map(|d| d.to_formatted_string())
+            .collect();
+        assert_eq!(
+            rendered.lines().take(4).collect::<Vec<&str>>(),
+            vec![
+                "2024-06-02 * \"PATH TAPP PAYGO\"",
+                "  description: \"PATH TAPP PAYGO\"",
+                "  local-amount: \"-1.00 USD\"",
+                "  Expenses:GBP:Monzo:Personal:Transport                    0.80 GBP",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_missing_csv_file() {
+        let dir = TempDir::new().unwrap();
+
+        let result = process_csv_file(&dir.child("missing.csv"), &personal());
+
+        assert!(matches!(result, Err(Error::ApplicationError(_))));
+    }
+}
@@ -53,7 +53,7 @@ fn get_data_folder_from_user() -> Result<PathBuf, Error> {
     let home_dir = dirs::document_dir().unwrap();
     let default_installation_dir = home_dir.join("beancount").to_string_lossy().to_string();
     let root_folder: String = Input::new()
-        .with_prompt(&"Folder location?".green().to_string())
+        .with_prompt("Folder location?".green().to_string())
         .with_initial_text(default_installation_dir)
         .interact_text()
         .map_err(|e| Error::ApplicationError(e.to_string()))?;
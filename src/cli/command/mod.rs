@@ -1,11 +1,13 @@
 pub mod generate;
 pub mod import_csv;
 pub mod init;
+pub mod monzo;
 pub mod server;
 pub mod sheets;
 
 pub use generate::generate;
 pub use import_csv::import;
 pub use init::init;
+pub use monzo::monzo;
 pub use server::server;
 pub use sheets::sheets;
@@ -0,0 +1,51 @@
+//! Lists Monzo API accounts with their balances and pots.
+
+use crate::beancount::money::from_minor;
+use crate::beancount::monzo::MonzoClient;
+use crate::beancount::Beancount;
+use crate::error::AppError as Error;
+
+pub async fn monzo(beancount: &Beancount) -> Result<(), Error> {
+    let settings = match &beancount.user_settings.monzo_api {
+        Some(settings) => settings,
+        None => {
+            println!("No `monzo_api` section in the configuration.");
+            return Ok(());
+        }
+    };
+
+    let mut client = MonzoClient::new(
+        &settings.base_url,
+        beancount.monzo_auth.credentials_file.clone(),
+    )?;
+
+    for account in client.accounts().await? {
+        let status = if account.closed { " (closed)" } else { "" };
+        let balance = client.balance(&account.id).await?;
+        let currency = account.currency.unwrap_or(balance.currency);
+        println!(
+            "{} {} {}{}: {} ({} including pots) {}",
+            account.id,
+            account.description,
+            account.account_type.unwrap_or_default(),
+            status,
+            from_minor(balance.balance, &currency),
+            from_minor(balance.total_balance, &currency),
+            currency,
+        );
+
+        for pot in client.pots(&account.id).await? {
+            let status = if pot.deleted { " (deleted)" } else { "" };
+            println!(
+                "  {} {}{}: {} {}",
+                pot.id,
+                pot.name,
+                status,
+                from_minor(pot.balance, &pot.currency),
+                pot.currency
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -1,22 +1,32 @@
 //! Run a server to periodically fetch transactions from Monzo and write them to a
 //! Beancount file.
 
+use std::future::Future;
+
 use crate::{beancount::Beancount, error::AppError as Error};
 use tokio::signal;
 use tokio::time::{self, Duration};
 
 pub async fn server(beancount: &Beancount, interval_secs: u64) -> Result<(), Error> {
-    if let Err(e) = generate_bean_periodically(beancount, interval_secs).await {
+    let shutdown = async {
+        let _ = signal::ctrl_c().await;
+        println!("Received Ctrl-C, shutting down.");
+    };
+
+    if let Err(e) = generate_bean_periodically(beancount, interval_secs, shutdown).await {
         eprintln!("Error: {:?}", e);
     }
     Ok(())
 }
 
+// Regenerate every `interval_secs` until `shutdown` completes.
 async fn generate_bean_periodically(
     beancount: &Beancount,
     interval_secs: u64,
+    shutdown: impl Future<Output = ()>,
 ) -> Result<(), Error> {
     let mut interval = time::interval(Duration::from_secs(interval_secs));
+    tokio::pin!(shutdown);
 
     loop {
         println!("->> refreshing...");
@@ -26,12 +36,42 @@ async fn generate_bean_periodically(
                     eprintln!("Error generating beanfile: {:?}", e);
                 }
             }
-            _ = signal::ctrl_c() => {
-                println!("Received Ctrl-C, shutting down.");
-                break;
-            }
+            _ = &mut shutdown => break,
         }
     }
 
     Ok(())
 }
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use temp_dir::TempDir;
+
+    use crate::beancount::generate::tests::test_beancount;
+    use crate::beancount::google::tests::mock_server;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_generate_until_shutdown() {
+        // Arrange
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let beancount = test_beancount(&dir, &server);
+        // the first tick is immediate, so this leaves time for one generation
+        let shutdown = time::sleep(Duration::from_millis(500));
+
+        // Act
+        generate_bean_periodically(&beancount, 60, shutdown)
+            .await
+            .unwrap();
+
+        // Assert
+        let main_file = fs::read_to_string(&beancount.data_file_paths.main_file).unwrap();
+        assert!(main_file.contains("2024-06-02 ! \"Tesco\""));
+    }
+}
@@ -5,15 +5,33 @@ use crate::beancount::Beancount;
 use crate::error::AppError as Error;
 
 pub async fn sheets(beancount: &Beancount) -> Result<(), Error> {
-    match &beancount.user_settings.googlesheet_accounts {
-        Some(accounts) => {
-            for account in accounts {
-                let google_sheet = GoogleSheet::new(account.clone()).await?;
-                println!("{:?}", google_sheet.sheets().await?);
-            }
+    if let Some(accounts) = &beancount.user_settings.googlesheet_accounts {
+        for account in accounts {
+            let google_sheet = GoogleSheet::new(&beancount.google_api, account.clone()).await?;
+            println!("{:?}", google_sheet.sheets().await?);
         }
-        None => {}
     }
 
     Ok(())
 }
+
+// -- Tests ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use temp_dir::TempDir;
+
+    use crate::beancount::generate::tests::test_beancount;
+    use crate::beancount::google::tests::mock_server;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn should_list_configured_sheets() {
+        let server = mock_server().await;
+        let dir = TempDir::new().unwrap();
+        let beancount = test_beancount(&dir, &server);
+
+        assert!(sheets(&beancount).await.is_ok());
+    }
+}
@@ -2,7 +2,7 @@
 
 pub mod command;
 
-use clap::{command, Parser, Subcommand};
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -18,11 +18,17 @@ pub enum Commands {
     /// Initialise the apo
     Init {},
     /// Generate beancount files
-    Generate {},
+    Generate {
+        /// Render from the local transaction store without fetching
+        #[arg(long)]
+        offline: bool,
+    },
     /// List sheet names
     Sheets {},
     /// Import CSV files
     Import {},
+    /// List Monzo API accounts, balances and pots
+    Monzo {},
     /// Server
     Server {
         /// Interval in seconds
@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use serde::Deserialize;
 
+use crate::beancount::google::auth::GoogleAuth;
+
 #[derive(Debug, Deserialize)]
 pub struct Settings {
     pub application: ApplicationSettings,
@@ -11,6 +13,10 @@ pub struct Settings {
 pub struct ApplicationSettings {
     pub data_dir: PathBuf,
     pub secrets_dir: PathBuf,
+    /// The root URL of the Google Sheets API, if not Google's.
+    pub google_api_url: Option<String>,
+    #[serde(default)]
+    pub google_auth: GoogleAuth,
 }
 
 pub fn get_configuration() -> Result<Settings, config::ConfigError> {
@@ -12,14 +12,59 @@ pub enum AppError {
     YamlError(#[from] serde_yaml::Error),
 
     #[error("Configuration error")]
-    ConfigurationError(#[from] config::ConfigError),
+    ConfigurationError(Box<config::ConfigError>),
 
     #[error("Failed parse sheets: {0}")]
-    GoogleError(#[from] google_sheets4::Error),
+    GoogleError(Box<google_sheets4::Error>),
+
+    #[error("Failed to parse JSON: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("HTTP request failed: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("Transaction store error: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+
+    #[error("Google credentials not found: {0}")]
+    GoogleCredentialsMissing(String),
+
+    #[error("Invalid Google credentials in {0}: {1}")]
+    GoogleCredentialsInvalid(String, String),
+
+    #[error("Monzo API error: {0}")]
+    MonzoApiError(String),
+
+    #[error("Monzo credentials not found: {0}")]
+    MonzoCredentialsMissing(String),
+
+    #[error("Invalid Monzo credentials in {0}: {1}")]
+    MonzoCredentialsInvalid(String, String),
 
     #[error("Failed to parse split category: {0}")]
     CategoryParseError(String),
 
+    #[error("Category split of transaction {0} totals {1}, but the transaction amount is {2}")]
+    CategorySplitTotalError(String, String, String),
+
+    #[error("{0} is missing required columns: {1}")]
+    MissingColumnsError(String, String),
+
+    #[error("{0} row {1}, column \"{2}\": {3}")]
+    RowParseError(String, usize, String, String),
+
+    #[error("{0} {1} is too large to store")]
+    AmountRangeError(String, String),
+
+    #[error("{0}: {1}")]
+    TransactionPostingError(String, String),
+
+    #[error("Beancount parse error on line {0}: {1}")]
+    BeancountParseError(usize, String),
+
+    #[error("Account name template \"{0}\": {1}")]
+    AccountTemplateError(String, String),
+
     #[error("Command aborted")]
     _AbortError,
 
@@ -32,3 +77,15 @@ pub enum AppError {
     #[error("Application error: {0}")]
     ApplicationError(String),
 }
+
+impl From<config::ConfigError> for AppError {
+    fn from(e: config::ConfigError) -> Self {
+        AppError::ConfigurationError(Box::new(e))
+    }
+}
+
+impl From<google_sheets4::Error> for AppError {
+    fn from(e: google_sheets4::Error) -> Self {
+        AppError::GoogleError(Box::new(e))
+    }
+}
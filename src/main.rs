@@ -55,6 +55,8 @@ mod cli;
 mod configuration;
 mod error;
 
+use beancount::google::GoogleApi;
+use beancount::monzo::MonzoAuth;
 use clap::Parser;
 use cli::{command, Cli, Commands};
 use colored::Colorize;
@@ -67,7 +69,7 @@ async fn main() -> Result<(), Error> {
 
     println!("->> {:#?}", config);
 
-    let bc = match beancount::Beancount::with_data_dir(config.application.data_dir) {
+    let bc = match beancount::Beancount::with_data_dir(config.application.data_dir.clone()) {
         Ok(b) => b,
         Err(Error::ConfigurationError(_)) => {
             println!(
@@ -78,6 +80,9 @@ async fn main() -> Result<(), Error> {
         }
         Err(e) => return Err(e),
     };
+    let bc = bc
+        .with_google_api(GoogleApi::from(&config.application))
+        .with_monzo_auth(MonzoAuth::from(&config.application));
 
     let cli = Cli::parse();
 
@@ -87,7 +92,7 @@ async fn main() -> Result<(), Error> {
             Err(e) => eprintln!("Error: {}", e),
         },
 
-        Commands::Generate {} => match command::generate(&bc).await {
+        Commands::Generate { offline } => match command::generate(&bc, *offline).await {
             Ok(_) => {}
             Err(e) => eprintln!("Error: {}", e),
         },
@@ -101,6 +106,10 @@ async fn main() -> Result<(), Error> {
             Ok(_) => {}
             Err(e) => eprintln!("Error: {}", e),
         },
+        Commands::Monzo {} => match command::monzo(&bc).await {
+            Ok(_) => {}
+            Err(e) => eprintln!("Error: {}", e),
+        },
         Commands::Server { interval_secs } => match command::server(&bc, *interval_secs).await {
             Ok(_) => {}
             Err(e) => eprintln!("Error: {}", e),